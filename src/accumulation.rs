@@ -0,0 +1,427 @@
+//! Species accumulation curves and species richness estimators.
+//!
+//! The sampling unit is a camera-day: a deployment on a single calendar day (in the timezone
+//! of the deployment start). Sites can be compared by grouping camera-days per deployment,
+//! location or deployment group.
+
+use crate::effort::{EffortOptions, Period};
+use crate::independence::IndependenceOptions;
+use crate::rai::RaiGroupBy;
+use crate::{Package, WriteDataPackageCsv};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Label of the site when camera-days are not grouped.
+const ALL_SITES: &str = "all";
+
+/// Number of incidences at or below which a species is considered infrequent by ICE.
+const ICE_RARE_THRESHOLD: usize = 10;
+
+/// Options for the accumulation and richness computations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccumulationOptions {
+    /// Grouping of camera-days into sites. The whole package is a single site (`all`) if
+    /// `None`.
+    pub group_by: Option<RaiGroupBy>,
+
+    /// Number of random orderings of the camera-days averaged by [`Package::accumulation`].
+    pub permutations: usize,
+
+    /// Seed of the random number generator used to order the camera-days.
+    pub seed: u64,
+
+    /// Options used to derive the independent detections.
+    pub independence: IndependenceOptions,
+
+    /// Options used to compute effort.
+    pub effort: EffortOptions,
+}
+
+impl Default for AccumulationOptions {
+    fn default() -> Self {
+        AccumulationOptions {
+            group_by: None,
+            permutations: 100,
+            seed: 0,
+            independence: IndependenceOptions::default(),
+            effort: EffortOptions::default(),
+        }
+    }
+}
+
+/// Point of a species accumulation curve.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AccumulationPoint {
+    /// Site the curve belongs to.
+    pub site: String,
+
+    /// Number of camera-days sampled.
+    pub units: usize,
+
+    /// Mean active camera-days sampled (camera-days at the start or end of a deployment can be
+    /// partial).
+    #[serde(rename = "cameraDays")]
+    pub camera_days: f64,
+
+    /// Mean number of species.
+    pub richness: f64,
+
+    /// Standard deviation of the number of species, if estimated.
+    pub sd: Option<f64>,
+}
+
+impl WriteDataPackageCsv<AccumulationPoint> for Vec<AccumulationPoint> {}
+
+/// Observed and estimated species richness of a site.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Richness {
+    /// Site.
+    pub site: String,
+
+    /// Number of camera-days.
+    pub units: usize,
+
+    /// Active camera-days.
+    #[serde(rename = "cameraDays")]
+    pub camera_days: f64,
+
+    /// Number of detected species.
+    pub observed: usize,
+
+    /// Bias-corrected Chao1 (abundance based, using detections as abundance).
+    pub chao1: f64,
+
+    /// Bias-corrected Chao2 (incidence based).
+    pub chao2: f64,
+
+    /// First-order jackknife (incidence based).
+    pub jackknife1: f64,
+
+    /// Second-order jackknife (incidence based).
+    pub jackknife2: f64,
+
+    /// Incidence-based coverage estimator.
+    pub ice: f64,
+}
+
+impl WriteDataPackageCsv<Richness> for Vec<Richness> {}
+
+/// Camera-day with the number of detections per species.
+struct Unit {
+    camera_days: f64,
+    detections: BTreeMap<String, usize>,
+}
+
+impl Package {
+    /// Camera-days per site.
+    fn sampling_units(&self, options: &AccumulationOptions) -> BTreeMap<String, Vec<Unit>> {
+        let mut detections: HashMap<(String, String), BTreeMap<String, usize>> = HashMap::new();
+        for detection in self.independent_detections(&options.independence) {
+            let offset = match self.deployment(&detection.deployment_id) {
+                Some(deployment) => deployment.deployment_start.timezone(),
+                None => continue,
+            };
+            let day = detection
+                .timestamp
+                .with_timezone(&offset)
+                .format("%Y-%m-%d")
+                .to_string();
+            *detections
+                .entry((detection.deployment_id, day))
+                .or_default()
+                .entry(detection.scientific_name)
+                .or_insert(0) += 1;
+        }
+
+        let mut sites: BTreeMap<String, Vec<Unit>> = BTreeMap::new();
+        for effort in self.effort_by_period(Period::Day, &options.effort) {
+            let deployment = match self.deployment(&effort.deployment_id) {
+                Some(deployment) => deployment,
+                None => continue,
+            };
+            let labels = match &options.group_by {
+                Some(group_by) => group_by.sites(deployment),
+                None => vec![ALL_SITES.to_string()],
+            };
+            let day_detections = detections
+                .get(&(
+                    effort.deployment_id.clone(),
+                    effort.period.clone().unwrap_or_default(),
+                ))
+                .cloned()
+                .unwrap_or_default();
+            for label in labels {
+                sites.entry(label).or_default().push(Unit {
+                    camera_days: effort.camera_days,
+                    detections: day_detections.clone(),
+                });
+            }
+        }
+
+        sites
+    }
+
+    /// Randomised species accumulation curves per site: the number of species as a function
+    /// of the number of camera-days, averaged over random orderings of the camera-days.
+    pub fn accumulation(&self, options: &AccumulationOptions) -> Vec<AccumulationPoint> {
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        let mut points = Vec::new();
+        for (site, units) in self.sampling_units(options) {
+            let n = units.len();
+            let permutations = options.permutations.max(1);
+            let mut richness = vec![Vec::with_capacity(permutations); n];
+            let mut camera_days = vec![0.0; n];
+
+            let mut order: Vec<usize> = (0..n).collect();
+            for _ in 0..permutations {
+                order.shuffle(&mut rng);
+                let mut species: BTreeSet<&str> = BTreeSet::new();
+                let mut days = 0.0;
+                for (i, unit) in order.iter().map(|i| &units[*i]).enumerate() {
+                    species.extend(unit.detections.keys().map(|s| s.as_str()));
+                    days += unit.camera_days;
+                    richness[i].push(species.len() as f64);
+                    camera_days[i] += days / permutations as f64;
+                }
+            }
+
+            for (i, values) in richness.into_iter().enumerate() {
+                let mean = values.iter().sum::<f64>() / values.len() as f64;
+                let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>()
+                    / (values.len().max(2) - 1) as f64;
+                points.push(AccumulationPoint {
+                    site: site.clone(),
+                    units: i + 1,
+                    camera_days: camera_days[i],
+                    richness: mean,
+                    sd: Some(variance.sqrt()),
+                });
+            }
+        }
+
+        points
+    }
+
+    /// Sample-based rarefaction curves per site: the expected number of species in `t`
+    /// camera-days drawn without replacement, computed analytically.
+    pub fn rarefaction(&self, options: &AccumulationOptions) -> Vec<AccumulationPoint> {
+        let mut points = Vec::new();
+        for (site, units) in self.sampling_units(options) {
+            let n = units.len();
+            let incidences = incidences(&units);
+            let mean_days = units.iter().map(|u| u.camera_days).sum::<f64>() / n as f64;
+
+            for t in 1..=n {
+                // Probability that a species found in q of n units is absent from t of them:
+                // C(n - q, t) / C(n, t), computed as a running product.
+                let richness: f64 = incidences
+                    .values()
+                    .map(|q| {
+                        let absent = (0..t)
+                            .map(|i| {
+                                let remaining = n as f64 - *q as f64 - i as f64;
+                                (remaining / (n - i) as f64).max(0.0)
+                            })
+                            .product::<f64>();
+                        1.0 - absent
+                    })
+                    .sum();
+                points.push(AccumulationPoint {
+                    site: site.clone(),
+                    units: t,
+                    camera_days: mean_days * t as f64,
+                    richness,
+                    sd: None,
+                });
+            }
+        }
+
+        points
+    }
+
+    /// Observed species richness and richness estimators per site.
+    pub fn richness(&self, options: &AccumulationOptions) -> Vec<Richness> {
+        self.sampling_units(options)
+            .into_iter()
+            .map(|(site, units)| {
+                let incidences = incidences(&units);
+                let mut abundances: BTreeMap<&str, usize> = BTreeMap::new();
+                for unit in &units {
+                    for (species, n) in &unit.detections {
+                        *abundances.entry(species).or_insert(0) += n;
+                    }
+                }
+
+                let observed = incidences.len();
+                let s = observed as f64;
+                let m = units.len() as f64;
+                let f1 = species_found(abundances.values(), 1);
+                let f2 = species_found(abundances.values(), 2);
+                let q1 = species_found(incidences.values(), 1);
+                let q2 = species_found(incidences.values(), 2);
+                let n: f64 = abundances.values().sum::<usize>() as f64;
+
+                let chao1 = if n > 0.0 {
+                    s + (n - 1.0) / n * f1 * (f1 - 1.0).max(0.0) / (2.0 * (f2 + 1.0))
+                } else {
+                    s
+                };
+                let (chao2, jackknife1, jackknife2) = if m >= 2.0 {
+                    (
+                        s + (m - 1.0) / m * q1 * (q1 - 1.0).max(0.0) / (2.0 * (q2 + 1.0)),
+                        s + q1 * (m - 1.0) / m,
+                        s + q1 * (2.0 * m - 3.0) / m - q2 * (m - 2.0).powi(2) / (m * (m - 1.0)),
+                    )
+                } else {
+                    (s, s, s)
+                };
+
+                Richness {
+                    site,
+                    units: units.len(),
+                    camera_days: units.iter().map(|u| u.camera_days).sum(),
+                    observed,
+                    chao1,
+                    chao2,
+                    jackknife1,
+                    jackknife2,
+                    ice: ice(&units, &incidences),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Number of camera-days each species was detected in.
+fn incidences(units: &[Unit]) -> BTreeMap<&str, usize> {
+    let mut incidences = BTreeMap::new();
+    for unit in units {
+        for species in unit.detections.keys() {
+            *incidences.entry(species.as_str()).or_insert(0) += 1;
+        }
+    }
+    incidences
+}
+
+/// Number of species found exactly `k` times.
+fn species_found<'a>(counts: impl Iterator<Item = &'a usize>, k: usize) -> f64 {
+    counts.filter(|c| **c == k).count() as f64
+}
+
+/// Incidence-based coverage estimator (Chao & Lee 1992, Lee & Chao 1994).
+fn ice(units: &[Unit], incidences: &BTreeMap<&str, usize>) -> f64 {
+    let infrequent: BTreeMap<&str, usize> = incidences
+        .iter()
+        .filter(|(_, q)| **q <= ICE_RARE_THRESHOLD)
+        .map(|(s, q)| (*s, *q))
+        .collect();
+    let frequent = (incidences.len() - infrequent.len()) as f64;
+    let s_infrequent = infrequent.len() as f64;
+    let n_infrequent: f64 = infrequent.values().sum::<usize>() as f64;
+    let q1 = infrequent.values().filter(|q| **q == 1).count() as f64;
+
+    let coverage = 1.0 - q1 / n_infrequent;
+    if s_infrequent == 0.0 || coverage <= 0.0 {
+        // No infrequent species, or all of them are uniques: the estimator is undefined and
+        // the observed richness is returned.
+        return incidences.len() as f64;
+    }
+
+    let m_infrequent = units
+        .iter()
+        .filter(|u| {
+            u.detections
+                .keys()
+                .any(|s| infrequent.contains_key(s.as_str()))
+        })
+        .count() as f64;
+    let sum: f64 = (1..=ICE_RARE_THRESHOLD)
+        .map(|j| {
+            let qj = infrequent.values().filter(|q| **q == j).count() as f64;
+            (j * (j - 1)) as f64 * qj
+        })
+        .sum();
+    let gamma = if m_infrequent > 1.0 {
+        (s_infrequent / coverage * m_infrequent / (m_infrequent - 1.0) * sum / n_infrequent.powi(2)
+            - 1.0)
+            .max(0.0)
+    } else {
+        0.0
+    };
+
+    frequent + s_infrequent / coverage + q1 / coverage * gamma
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn unit(species: &[&str]) -> Unit {
+        Unit {
+            camera_days: 1.0,
+            detections: species.iter().map(|s| (s.to_string(), 1)).collect(),
+        }
+    }
+
+    #[test]
+    fn ice_without_uniques_equals_observed() {
+        let units = vec![unit(&["a", "b"]), unit(&["a", "b"])];
+        assert_eq!(ice(&units, &incidences(&units)), 2.0);
+    }
+
+    #[test]
+    fn richness_estimators() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let richness = package.richness(&AccumulationOptions::default());
+        assert_eq!(richness.len(), 1);
+
+        let all = &richness[0];
+        let species: BTreeSet<_> = package
+            .independent_detections(&IndependenceOptions::default())
+            .into_iter()
+            .map(|d| d.scientific_name)
+            .collect();
+        assert_eq!(all.observed, species.len());
+        for estimate in [
+            all.chao1,
+            all.chao2,
+            all.jackknife1,
+            all.jackknife2,
+            all.ice,
+        ] {
+            assert!(estimate >= all.observed as f64);
+        }
+    }
+
+    #[test]
+    fn accumulation_is_reproducible() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let options = AccumulationOptions {
+            group_by: Some(RaiGroupBy::Deployment),
+            permutations: 20,
+            seed: 42,
+            ..Default::default()
+        };
+        let curve = package.accumulation(&options);
+        assert_eq!(curve, package.accumulation(&options));
+
+        for site in package.richness(&options) {
+            let points: Vec<_> = curve.iter().filter(|p| p.site == site.site).collect();
+            assert_eq!(points.len(), site.units);
+            assert!((points.last().unwrap().richness - site.observed as f64).abs() < 1e-9);
+            assert!(points.windows(2).all(|w| w[0].richness <= w[1].richness));
+        }
+    }
+
+    #[test]
+    fn rarefaction_reaches_observed() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let options = AccumulationOptions::default();
+        let curve = package.rarefaction(&options);
+        let observed = package.richness(&options)[0].observed as f64;
+        assert!((curve.last().unwrap().richness - observed).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,351 @@
+//! Diel activity patterns and activity overlap between species.
+//!
+//! Detection times are expressed in radians of the day (0 at midnight, π at noon), either on
+//! the clock or relative to the sun. Densities are estimated with a von Mises kernel, and
+//! overlap coefficients follow Ridout & Linkie (2009), as implemented in the R package
+//! `overlap`.
+
+use crate::independence::IndependenceOptions;
+use crate::sun::sun_time;
+use crate::timezone::to_timezone;
+use crate::Package;
+use chrono::{DateTime, FixedOffset, Timelike};
+use chrono_tz::Tz;
+use std::collections::{BTreeMap, HashMap};
+use std::f64::consts::PI;
+
+/// Scale on which the time of day of a detection is expressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeScale {
+    /// Clock time, in the timezone of the deployment (see [`ActivityOptions::timezones`]),
+    /// or else in the timezone of the deployment start.
+    Clock,
+
+    /// Time relative to sunrise (π/2) and sunset (3π/2), computed from the deployment
+    /// latitude and longitude. Detections at deployments without coordinates, or during polar
+    /// day or night, are skipped.
+    Sun,
+}
+
+/// Options for [`Package::activity_patterns`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityOptions {
+    /// Scale on which the time of day is expressed.
+    pub time_scale: TimeScale,
+
+    /// Options used to derive the independent detections.
+    pub independence: IndependenceOptions,
+
+    /// IANA timezone per deploymentID, used for local wall-clock times so daylight saving
+    /// time is taken into account (see [`Package::deployment_timezones`]).
+    pub timezones: HashMap<String, Tz>,
+}
+
+impl Default for ActivityOptions {
+    fn default() -> Self {
+        ActivityOptions {
+            time_scale: TimeScale::Clock,
+            independence: IndependenceOptions::default(),
+            timezones: HashMap::new(),
+        }
+    }
+}
+
+/// Times of day at which a species was detected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityPattern {
+    /// Scientific name of the species.
+    pub scientific_name: String,
+
+    /// Times of day of the detections, in radians.
+    pub times: Vec<f64>,
+}
+
+impl ActivityPattern {
+    /// Concentration parameter of the von Mises kernel, using the rule of Taylor (2008) with a
+    /// von Mises fit to the data. `adjust` multiplies the bandwidth: values above 1 give
+    /// smoother densities.
+    pub fn bandwidth(&self, adjust: f64) -> f64 {
+        bandwidth(&self.times) / adjust
+    }
+
+    /// Kernel density estimate at each point of the grid (in radians). Integrates to 1 over
+    /// the day.
+    pub fn density(&self, grid: &[f64], adjust: f64) -> Vec<f64> {
+        let kappa = self.bandwidth(adjust);
+        grid.iter()
+            .map(|x| von_mises_sum(&self.times, *x, kappa))
+            .collect()
+    }
+}
+
+/// Estimator of the overlap coefficient.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapEstimator {
+    /// Δ1, recommended for small samples (fewer than 50 detections). Uses a bandwidth
+    /// adjustment of 0.8.
+    Dhat1,
+
+    /// Δ4, recommended for samples of 75 detections or more. Uses a bandwidth adjustment of 1.
+    Dhat4,
+}
+
+/// Overlap coefficient between the activity patterns of two species.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityOverlap {
+    /// Scientific name of the first species.
+    pub species_a: String,
+
+    /// Scientific name of the second species.
+    pub species_b: String,
+
+    /// Δ1 estimate.
+    pub dhat1: f64,
+
+    /// Δ4 estimate.
+    pub dhat4: f64,
+}
+
+/// Number of grid points used to integrate Δ1.
+const GRID_SIZE: usize = 128;
+
+/// Overlap coefficient between two samples of times of day (in radians).
+pub fn overlap(a: &[f64], b: &[f64], estimator: OverlapEstimator) -> f64 {
+    match estimator {
+        OverlapEstimator::Dhat1 => {
+            let kappa_a = bandwidth(a) / 0.8;
+            let kappa_b = bandwidth(b) / 0.8;
+            let total: f64 = (0..GRID_SIZE)
+                .map(|i| 2.0 * PI * i as f64 / GRID_SIZE as f64)
+                .map(|x| von_mises_sum(a, x, kappa_a).min(von_mises_sum(b, x, kappa_b)))
+                .sum();
+            total * 2.0 * PI / GRID_SIZE as f64
+        }
+        OverlapEstimator::Dhat4 => {
+            let kappa_a = bandwidth(a);
+            let kappa_b = bandwidth(b);
+            let ratio = |x: &[f64], f: &[f64], kappa_f: f64, g: &[f64], kappa_g: f64| {
+                x.iter()
+                    .map(|x| {
+                        (von_mises_sum(g, *x, kappa_g) / von_mises_sum(f, *x, kappa_f)).min(1.0)
+                    })
+                    .sum::<f64>()
+                    / x.len() as f64
+            };
+            (ratio(a, a, kappa_a, b, kappa_b) + ratio(b, b, kappa_b, a, kappa_a)) / 2.0
+        }
+    }
+}
+
+/// Clock time of day of a timestamp in its own timezone, in radians.
+pub fn clock_time(timestamp: &DateTime<FixedOffset>) -> f64 {
+    timestamp.num_seconds_from_midnight() as f64 / 86400.0 * 2.0 * PI
+}
+
+impl Package {
+    /// Times of day of the independent detections per species, ordered by scientific name.
+    pub fn activity_patterns(&self, options: &ActivityOptions) -> Vec<ActivityPattern> {
+        let mut times: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        for detection in self.independent_detections(&options.independence) {
+            let deployment = match self.deployment(&detection.deployment_id) {
+                Some(deployment) => deployment,
+                None => continue,
+            };
+            let time = match options.time_scale {
+                TimeScale::Clock => Some(match options.timezones.get(&detection.deployment_id) {
+                    Some(timezone) => clock_time(&to_timezone(&detection.timestamp, timezone)),
+                    None => clock_time(
+                        &detection
+                            .timestamp
+                            .with_timezone(&deployment.deployment_start.timezone()),
+                    ),
+                }),
+                TimeScale::Sun => match (deployment.latitude, deployment.longitude) {
+                    (Some(latitude), Some(longitude)) => {
+                        sun_time(&detection.timestamp, latitude, longitude)
+                    }
+                    _ => None,
+                },
+            };
+            if let Some(time) = time {
+                times
+                    .entry(detection.scientific_name)
+                    .or_default()
+                    .push(time);
+            }
+        }
+
+        times
+            .into_iter()
+            .map(|(scientific_name, times)| ActivityPattern {
+                scientific_name,
+                times,
+            })
+            .collect()
+    }
+
+    /// Overlap coefficients between every pair of species with at least `min_detections`
+    /// detections.
+    pub fn activity_overlaps(
+        &self,
+        options: &ActivityOptions,
+        min_detections: usize,
+    ) -> Vec<ActivityOverlap> {
+        let patterns: Vec<ActivityPattern> = self
+            .activity_patterns(options)
+            .into_iter()
+            .filter(|p| p.times.len() >= min_detections.max(2))
+            .collect();
+
+        let mut overlaps = Vec::new();
+        for (i, a) in patterns.iter().enumerate() {
+            for b in &patterns[i + 1..] {
+                overlaps.push(ActivityOverlap {
+                    species_a: a.scientific_name.clone(),
+                    species_b: b.scientific_name.clone(),
+                    dhat1: overlap(&a.times, &b.times, OverlapEstimator::Dhat1),
+                    dhat4: overlap(&a.times, &b.times, OverlapEstimator::Dhat4),
+                });
+            }
+        }
+
+        overlaps
+    }
+}
+
+/// Mean of von Mises densities centred on the sample, evaluated at `x`.
+fn von_mises_sum(sample: &[f64], x: f64, kappa: f64) -> f64 {
+    // exp(κ cos θ) / I0(κ) is computed as exp(κ (cos θ - 1)) / (I0(κ) e^-κ) to avoid overflow.
+    let scale = 2.0 * PI * bessel_ie(0, kappa);
+    sample
+        .iter()
+        .map(|xi| (kappa * ((x - xi).cos() - 1.0)).exp())
+        .sum::<f64>()
+        / (scale * sample.len() as f64)
+}
+
+/// Bandwidth (von Mises concentration) following Taylor (2008):
+/// `[3 n κ² I2(2κ) / (4 π^½ I0(κ)²)]^(2/5)`, with κ the maximum likelihood estimate of a
+/// von Mises distribution fitted to the sample.
+fn bandwidth(sample: &[f64]) -> f64 {
+    let n = sample.len() as f64;
+    let kappa = von_mises_kappa(sample);
+    // I2(2κ) / I0(κ)² equals the ratio of the exponentially scaled functions.
+    let ratio = bessel_ie(2, 2.0 * kappa) / bessel_ie(0, kappa).powi(2);
+    (3.0 * n * kappa.powi(2) * ratio / (4.0 * PI.sqrt())).powf(0.4)
+}
+
+/// Maximum likelihood estimate of the von Mises concentration, using the approximation of
+/// Best & Fisher (1981).
+fn von_mises_kappa(sample: &[f64]) -> f64 {
+    let n = sample.len() as f64;
+    let (sin, cos) = sample
+        .iter()
+        .fold((0.0, 0.0), |(s, c), x| (s + x.sin(), c + x.cos()));
+    let r = (sin * sin + cos * cos).sqrt() / n;
+
+    if r < 0.53 {
+        2.0 * r + r.powi(3) + 5.0 * r.powi(5) / 6.0
+    } else if r < 0.85 {
+        -0.4 + 1.39 * r + 0.43 / (1.0 - r)
+    } else {
+        1.0 / (r.powi(3) - 4.0 * r.powi(2) + 3.0 * r)
+    }
+}
+
+/// Exponentially scaled modified Bessel function of the first kind, `I_n(x) e^-x`.
+fn bessel_ie(n: u32, x: f64) -> f64 {
+    if x > 50.0 {
+        // Asymptotic expansion for large arguments.
+        let mu = 4.0 * (n as f64).powi(2);
+        let mut term = 1.0;
+        let mut sum = 1.0;
+        for k in 1..8 {
+            term *= -(mu - (2.0 * k as f64 - 1.0).powi(2)) / (k as f64 * 8.0 * x);
+            sum += term;
+        }
+        return sum / (2.0 * PI * x).sqrt();
+    }
+
+    // Power series: sum of (x/2)^(2k+n) / (k! (k+n)!).
+    let half = x / 2.0;
+    let mut term = half.powi(n as i32) / (1..=n).map(|i| i as f64).product::<f64>();
+    let mut sum = term;
+    for k in 1..500 {
+        term *= half * half / (k as f64 * (k + n) as f64);
+        sum += term;
+        if term < sum * 1e-16 {
+            break;
+        }
+    }
+    sum * (-x).exp()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bessel_values() {
+        assert!((bessel_ie(0, 1.0) * 1f64.exp() - 1.266_065_877_752_008).abs() < 1e-12);
+        assert!((bessel_ie(2, 3.0) * 3f64.exp() - 2.245_212_440_929_95).abs() < 1e-12);
+        assert!((bessel_ie(0, 60.0) / bessel_ie(0, 49.99) - (49.99f64 / 60.0).sqrt()).abs() < 1e-2);
+    }
+
+    #[test]
+    fn density_integrates_to_one() {
+        let pattern = ActivityPattern {
+            scientific_name: "Vulpes vulpes".to_string(),
+            times: vec![0.1, 0.4, 5.9, 6.1, 1.0, 3.0],
+        };
+        let grid: Vec<f64> = (0..512).map(|i| 2.0 * PI * i as f64 / 512.0).collect();
+        let total: f64 = pattern.density(&grid, 1.0).iter().sum::<f64>() * 2.0 * PI / 512.0;
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn overlap_of_identical_and_disjoint_samples() {
+        let day: Vec<f64> = (0..40).map(|i| PI * 0.8 + i as f64 * 0.01).collect();
+        let night: Vec<f64> = (0..40).map(|i| 0.1 + i as f64 * 0.01).collect();
+
+        for estimator in [OverlapEstimator::Dhat1, OverlapEstimator::Dhat4] {
+            assert!((overlap(&day, &day, estimator) - 1.0).abs() < 1e-6);
+            assert!(overlap(&day, &night, estimator) < 0.05);
+        }
+    }
+
+    #[test]
+    fn fixture_activity_patterns() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let clock = package.activity_patterns(&ActivityOptions::default());
+        let sun = package.activity_patterns(&ActivityOptions {
+            time_scale: TimeScale::Sun,
+            ..Default::default()
+        });
+        assert!(!clock.is_empty());
+        assert_eq!(
+            clock.iter().map(|p| p.times.len()).sum::<usize>(),
+            sun.iter().map(|p| p.times.len()).sum::<usize>()
+        );
+        assert!(clock
+            .iter()
+            .flat_map(|p| &p.times)
+            .all(|t| (0.0..2.0 * PI).contains(t)));
+
+        let local = package.activity_patterns(&ActivityOptions {
+            timezones: package.deployment_timezones(&chrono_tz::Europe::Brussels),
+            ..Default::default()
+        });
+        // Detections after the start of daylight saving time shift by an hour, but none are
+        // dropped.
+        assert_eq!(
+            local.iter().map(|p| p.times.len()).collect::<Vec<_>>(),
+            clock.iter().map(|p| p.times.len()).collect::<Vec<_>>()
+        );
+
+        let overlaps = package.activity_overlaps(&ActivityOptions::default(), 2);
+        assert!(overlaps.iter().all(
+            |o| (0.0..=1.0 + 1e-9).contains(&o.dhat1) && (0.0..=1.0 + 1e-9).contains(&o.dhat4)
+        ));
+    }
+}
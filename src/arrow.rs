@@ -0,0 +1,647 @@
+//! Arrow record batches and Parquet files of the deployments, media and observations
+//! resources, for analytics tools such as DuckDB and Polars. Requires the `arrow` feature.
+//!
+//! Enumerations (e.g. `observationType`) are dictionary encoded and timestamps are stored as
+//! seconds since the Unix epoch in UTC. The UTC offset of each timestamp is stored in minutes
+//! in a companion column (e.g. `timestampOffset`), so timestamps read back keep their offset;
+//! without that column they are expressed in UTC. `exifData` is stored as a JSON string.
+
+use crate::util::name;
+use crate::{Deployment, Medium, Observation};
+use ::arrow::array::{
+    Array, ArrayRef, BooleanArray, DictionaryArray, Float64Array, Int16Array, Int64Array,
+    RecordBatch, StringArray, TimestampSecondArray, UInt32Array,
+};
+use ::arrow::compute::cast;
+use ::arrow::datatypes::{DataType, Field, Int8Type, Schema, SchemaRef, TimeUnit};
+use ::arrow::error::ArrowError;
+use ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::basic::Compression;
+use ::parquet::file::properties::WriterProperties;
+use chrono::{DateTime, FixedOffset};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Error type for reading and writing Parquet files.
+#[derive(Debug)]
+pub enum ParquetError {
+    Io(std::io::Error),
+    Arrow(ArrowError),
+    Parquet(::parquet::errors::ParquetError),
+}
+
+/// Conversion of a resource to and from Arrow record batches and Parquet files.
+pub trait ArrowTable: Sized {
+    /// Convert rows to a record batch.
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, ArrowError>;
+
+    /// Convert a record batch to rows. Columns are matched by name; optional columns may be
+    /// missing and compatible types (e.g. plain strings instead of dictionaries) are cast.
+    fn from_record_batch(batch: &RecordBatch) -> Result<Vec<Self>, ArrowError>;
+
+    /// Arrow schema of the resource.
+    fn schema() -> SchemaRef {
+        Self::to_record_batch(&[])
+            .map(|batch| batch.schema())
+            .unwrap_or_else(|_| Arc::new(Schema::empty()))
+    }
+
+    /// Write rows to a Parquet file (Snappy compressed).
+    fn to_parquet(rows: &[Self], path: &str) -> Result<(), ParquetError> {
+        let batch = Self::to_record_batch(rows).map_err(ParquetError::Arrow)?;
+        let file = std::fs::File::create(path).map_err(ParquetError::Io)?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(properties))
+            .map_err(ParquetError::Parquet)?;
+        writer.write(&batch).map_err(ParquetError::Parquet)?;
+        writer.close().map_err(ParquetError::Parquet)?;
+        Ok(())
+    }
+
+    /// Read rows from a Parquet file.
+    fn from_parquet(path: &str) -> Result<Vec<Self>, ParquetError> {
+        let file = std::fs::File::open(path).map_err(ParquetError::Io)?;
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .and_then(|builder| builder.build())
+            .map_err(ParquetError::Parquet)?;
+        let mut rows = Vec::new();
+        for batch in reader {
+            let batch = batch.map_err(ParquetError::Arrow)?;
+            rows.extend(Self::from_record_batch(&batch).map_err(ParquetError::Arrow)?);
+        }
+        Ok(rows)
+    }
+}
+
+/// Columns of a record batch under construction.
+struct Builder {
+    fields: Vec<Field>,
+    arrays: Vec<ArrayRef>,
+}
+
+impl Builder {
+    fn new() -> Self {
+        Builder {
+            fields: Vec::new(),
+            arrays: Vec::new(),
+        }
+    }
+
+    fn column(mut self, name: &str, nullable: bool, array: ArrayRef) -> Self {
+        self.fields
+            .push(Field::new(name, array.data_type().clone(), nullable));
+        self.arrays.push(array);
+        self
+    }
+
+    /// Timestamp column and its `<name>Offset` companion column.
+    fn timestamp<T>(
+        self,
+        name: &str,
+        nullable: bool,
+        rows: &[T],
+        value: impl Fn(&T) -> Option<DateTime<FixedOffset>>,
+    ) -> Self {
+        self.column(name, nullable, timestamp(rows, &value)).column(
+            &format!("{}Offset", name),
+            nullable,
+            offset(rows, &value),
+        )
+    }
+
+    fn build(self) -> Result<RecordBatch, ArrowError> {
+        RecordBatch::try_new(Arc::new(Schema::new(self.fields)), self.arrays)
+    }
+}
+
+fn utf8<T>(rows: &[T], value: impl Fn(&T) -> Option<&str>) -> ArrayRef {
+    Arc::new(rows.iter().map(value).collect::<StringArray>())
+}
+
+fn float64<T>(rows: &[T], value: impl Fn(&T) -> Option<f64>) -> ArrayRef {
+    Arc::new(rows.iter().map(value).collect::<Float64Array>())
+}
+
+fn uint32<T>(rows: &[T], value: impl Fn(&T) -> Option<u32>) -> ArrayRef {
+    Arc::new(rows.iter().map(value).collect::<UInt32Array>())
+}
+
+fn boolean<T>(rows: &[T], value: impl Fn(&T) -> Option<bool>) -> ArrayRef {
+    Arc::new(rows.iter().map(value).collect::<BooleanArray>())
+}
+
+fn timestamp<T>(rows: &[T], value: impl Fn(&T) -> Option<DateTime<FixedOffset>>) -> ArrayRef {
+    Arc::new(
+        rows.iter()
+            .map(|row| value(row).map(|t| t.timestamp()))
+            .collect::<TimestampSecondArray>()
+            .with_timezone("UTC"),
+    )
+}
+
+fn offset<T>(rows: &[T], value: impl Fn(&T) -> Option<DateTime<FixedOffset>>) -> ArrayRef {
+    Arc::new(
+        rows.iter()
+            .map(|row| value(row).map(|t| (t.offset().local_minus_utc() / 60) as i16))
+            .collect::<Int16Array>(),
+    )
+}
+
+fn dictionary<T, E: Serialize>(rows: &[T], value: impl Fn(&T) -> Option<&E>) -> ArrayRef {
+    let names: Vec<Option<String>> = rows.iter().map(|row| value(row).map(name)).collect();
+    Arc::new(
+        names
+            .iter()
+            .map(|name| name.as_deref())
+            .collect::<DictionaryArray<Int8Type>>(),
+    )
+}
+
+/// Columns of a record batch being read, cast to the types used by the getters.
+struct Columns {
+    arrays: HashMap<String, ArrayRef>,
+    rows: usize,
+}
+
+impl Columns {
+    fn new(batch: &RecordBatch) -> Result<Self, ArrowError> {
+        let mut arrays = HashMap::new();
+        for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+            let target = match field.data_type() {
+                DataType::Dictionary(_, _) | DataType::LargeUtf8 | DataType::Utf8View => {
+                    Some(DataType::Utf8)
+                }
+                DataType::Timestamp(unit, timezone)
+                    if *unit != TimeUnit::Second || timezone.is_none() =>
+                {
+                    Some(DataType::Timestamp(TimeUnit::Second, Some("UTC".into())))
+                }
+                data_type if data_type.is_integer() => Some(DataType::Int64),
+                DataType::Float16 | DataType::Float32 => Some(DataType::Float64),
+                _ => None,
+            };
+            let array = match target {
+                Some(target) => cast(array, &target)?,
+                None => array.clone(),
+            };
+            arrays.insert(field.name().clone(), array);
+        }
+        Ok(Columns {
+            arrays,
+            rows: batch.num_rows(),
+        })
+    }
+
+    fn array<A: 'static>(&self, name: &str, row: usize) -> Option<&A> {
+        let array = self.arrays.get(name)?;
+        if array.is_null(row) {
+            return None;
+        }
+        array.as_any().downcast_ref::<A>()
+    }
+
+    fn string(&self, name: &str, row: usize) -> Option<String> {
+        self.array::<StringArray>(name, row)
+            .map(|a| a.value(row).to_string())
+    }
+
+    fn float64(&self, name: &str, row: usize) -> Option<f64> {
+        self.array::<Float64Array>(name, row).map(|a| a.value(row))
+    }
+
+    fn uint32(&self, name: &str, row: usize) -> Option<u32> {
+        self.array::<Int64Array>(name, row)
+            .and_then(|a| u32::try_from(a.value(row)).ok())
+    }
+
+    fn boolean(&self, name: &str, row: usize) -> Option<bool> {
+        self.array::<BooleanArray>(name, row).map(|a| a.value(row))
+    }
+
+    fn timestamp(&self, name: &str, row: usize) -> Option<DateTime<FixedOffset>> {
+        let timestamp = self
+            .array::<TimestampSecondArray>(name, row)
+            .and_then(|a| DateTime::from_timestamp(a.value(row), 0))?;
+        let offset = self
+            .array::<Int64Array>(&format!("{}Offset", name), row)
+            .and_then(|a| FixedOffset::east_opt(i32::try_from(a.value(row)).ok()? * 60))
+            .unwrap_or(FixedOffset::east_opt(0)?);
+        Some(timestamp.with_timezone(&offset))
+    }
+
+    fn enumeration<E: DeserializeOwned>(&self, name: &str, row: usize) -> Option<E> {
+        serde_json::from_value(serde_json::Value::String(self.string(name, row)?)).ok()
+    }
+
+    fn json(&self, name: &str, row: usize) -> Option<serde_json::Value> {
+        serde_json::from_str(&self.string(name, row)?).ok()
+    }
+}
+
+fn required<V>(name: &str, value: Option<V>) -> Result<V, ArrowError> {
+    value.ok_or_else(|| {
+        ArrowError::InvalidArgumentError(format!("missing or invalid value in column {}", name))
+    })
+}
+
+impl ArrowTable for Deployment {
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, ArrowError> {
+        Builder::new()
+            .column(
+                "deploymentID",
+                false,
+                utf8(rows, |d| Some(&d.deployment_id)),
+            )
+            .column("locationID", true, utf8(rows, |d| d.location_id.as_deref()))
+            .column(
+                "locationName",
+                true,
+                utf8(rows, |d| d.location_name.as_deref()),
+            )
+            .column("latitude", true, float64(rows, |d| d.latitude))
+            .column("longitude", true, float64(rows, |d| d.longitude))
+            .column("locationRadius", true, float64(rows, |d| d.location_radius))
+            .timestamp("deploymentStart", false, rows, |d| Some(d.deployment_start))
+            .timestamp("deploymentEnd", false, rows, |d| Some(d.deployment_end))
+            .column("setupBy", true, utf8(rows, |d| d.setup_by.as_deref()))
+            .column("cameraID", true, utf8(rows, |d| d.camera_id.as_deref()))
+            .column(
+                "cameraModel",
+                true,
+                utf8(rows, |d| d.camera_model.as_deref()),
+            )
+            .column("cameraDelay", true, float64(rows, |d| d.camera_delay))
+            .column("cameraHeight", true, float64(rows, |d| d.camera_height))
+            .column("cameraDepth", true, float64(rows, |d| d.camera_depth))
+            .column("cameraAngle", true, float64(rows, |d| d.camera_angle))
+            .column("cameraHeading", true, float64(rows, |d| d.camera_heading))
+            .column(
+                "detectionDistance",
+                true,
+                float64(rows, |d| d.detection_distance),
+            )
+            .column(
+                "timestampIssues",
+                true,
+                boolean(rows, |d| d.timestamp_issues),
+            )
+            .column("baitUse", true, boolean(rows, |d| d.bait_use))
+            .column(
+                "featureType",
+                true,
+                dictionary(rows, |d| d.feature_type.as_ref()),
+            )
+            .column("habitat", true, utf8(rows, |d| d.habitat.as_deref()))
+            .column(
+                "deploymentGroups",
+                true,
+                utf8(rows, |d| d.deployment_groups.as_deref()),
+            )
+            .column("tags", true, utf8(rows, |d| d.tags.as_deref()))
+            .column("comments", true, utf8(rows, |d| d.comments.as_deref()))
+            .build()
+    }
+
+    fn from_record_batch(batch: &RecordBatch) -> Result<Vec<Self>, ArrowError> {
+        let c = Columns::new(batch)?;
+        (0..c.rows)
+            .map(|i| {
+                Ok(Deployment {
+                    deployment_id: required("deploymentID", c.string("deploymentID", i))?,
+                    location_id: c.string("locationID", i),
+                    location_name: c.string("locationName", i),
+                    latitude: c.float64("latitude", i),
+                    longitude: c.float64("longitude", i),
+                    location_radius: c.float64("locationRadius", i),
+                    deployment_start: required(
+                        "deploymentStart",
+                        c.timestamp("deploymentStart", i),
+                    )?,
+                    deployment_end: required("deploymentEnd", c.timestamp("deploymentEnd", i))?,
+                    setup_by: c.string("setupBy", i),
+                    camera_id: c.string("cameraID", i),
+                    camera_model: c.string("cameraModel", i),
+                    camera_delay: c.float64("cameraDelay", i),
+                    camera_height: c.float64("cameraHeight", i),
+                    camera_depth: c.float64("cameraDepth", i),
+                    camera_angle: c.float64("cameraAngle", i),
+                    camera_heading: c.float64("cameraHeading", i),
+                    detection_distance: c.float64("detectionDistance", i),
+                    timestamp_issues: c.boolean("timestampIssues", i),
+                    bait_use: c.boolean("baitUse", i),
+                    feature_type: c.enumeration("featureType", i),
+                    habitat: c.string("habitat", i),
+                    deployment_groups: c.string("deploymentGroups", i),
+                    tags: c.string("tags", i),
+                    comments: c.string("comments", i),
+                })
+            })
+            .collect()
+    }
+}
+
+impl ArrowTable for Medium {
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, ArrowError> {
+        let exif: Vec<Option<String>> = rows
+            .iter()
+            .map(|m| m.exif_data.as_ref().map(|exif| exif.to_string()))
+            .collect();
+        Builder::new()
+            .column("mediaID", false, utf8(rows, |m| Some(&m.media_id)))
+            .column(
+                "deploymentID",
+                false,
+                utf8(rows, |m| Some(&m.deployment_id)),
+            )
+            .column(
+                "captureMethod",
+                true,
+                dictionary(rows, |m| m.capture_method.as_ref()),
+            )
+            .timestamp("timestamp", false, rows, |m| Some(m.timestamp))
+            .column("filePath", false, utf8(rows, |m| Some(&m.file_path)))
+            .column("filePublic", false, boolean(rows, |m| Some(m.file_public)))
+            .column("fileName", true, utf8(rows, |m| m.file_name.as_deref()))
+            .column(
+                "fileMediatype",
+                false,
+                utf8(rows, |m| Some(&m.file_mediatype)),
+            )
+            .column("exifData", true, utf8(&exif, |e| e.as_deref()))
+            .column("favorite", true, boolean(rows, |m| m.favorite))
+            .column("comments", true, utf8(rows, |m| m.comments.as_deref()))
+            .build()
+    }
+
+    fn from_record_batch(batch: &RecordBatch) -> Result<Vec<Self>, ArrowError> {
+        let c = Columns::new(batch)?;
+        (0..c.rows)
+            .map(|i| {
+                Ok(Medium {
+                    media_id: required("mediaID", c.string("mediaID", i))?,
+                    deployment_id: required("deploymentID", c.string("deploymentID", i))?,
+                    capture_method: c.enumeration("captureMethod", i),
+                    timestamp: required("timestamp", c.timestamp("timestamp", i))?,
+                    file_path: required("filePath", c.string("filePath", i))?,
+                    file_public: required("filePublic", c.boolean("filePublic", i))?,
+                    file_name: c.string("fileName", i),
+                    file_mediatype: required("fileMediatype", c.string("fileMediatype", i))?,
+                    exif_data: c.json("exifData", i),
+                    favorite: c.boolean("favorite", i),
+                    comments: c.string("comments", i),
+                })
+            })
+            .collect()
+    }
+}
+
+impl ArrowTable for Observation {
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, ArrowError> {
+        Builder::new()
+            .column(
+                "observationID",
+                false,
+                utf8(rows, |o| Some(&o.observation_id)),
+            )
+            .column(
+                "deploymentID",
+                false,
+                utf8(rows, |o| Some(&o.deployment_id)),
+            )
+            .column("mediaID", true, utf8(rows, |o| o.media_id.as_deref()))
+            .column("eventID", true, utf8(rows, |o| o.event_id.as_deref()))
+            .timestamp("eventStart", true, rows, |o| o.event_start)
+            .timestamp("eventEnd", true, rows, |o| o.event_end)
+            .column(
+                "observationLevel",
+                false,
+                dictionary(rows, |o| Some(&o.observation_level)),
+            )
+            .column(
+                "observationType",
+                false,
+                dictionary(rows, |o| Some(&o.observation_type)),
+            )
+            .column(
+                "cameraSetupType",
+                true,
+                dictionary(rows, |o| o.camera_setup_type.as_ref()),
+            )
+            .column(
+                "scientificName",
+                true,
+                utf8(rows, |o| o.scientific_name.as_deref()),
+            )
+            .column("count", true, uint32(rows, |o| o.count))
+            .column(
+                "lifeStage",
+                true,
+                dictionary(rows, |o| o.life_stage.as_ref()),
+            )
+            .column("sex", true, dictionary(rows, |o| o.sex.as_ref()))
+            .column("behavior", true, utf8(rows, |o| o.behavior.as_deref()))
+            .column(
+                "individualID",
+                true,
+                utf8(rows, |o| o.individual_id.as_deref()),
+            )
+            .column(
+                "individualPositionRadius",
+                true,
+                float64(rows, |o| o.individual_position_radius),
+            )
+            .column(
+                "individualPositionAngle",
+                true,
+                float64(rows, |o| o.individual_position_angle),
+            )
+            .column(
+                "individualSpeed",
+                true,
+                float64(rows, |o| o.individual_speed),
+            )
+            .column("bboxX", true, float64(rows, |o| o.bbox_x))
+            .column("bboxY", true, float64(rows, |o| o.bbox_y))
+            .column("bboxWidth", true, float64(rows, |o| o.bbox_width))
+            .column("bboxHeight", true, float64(rows, |o| o.bbox_height))
+            .column(
+                "classificationMethod",
+                true,
+                dictionary(rows, |o| o.classification_method.as_ref()),
+            )
+            .column(
+                "classifiedBy",
+                true,
+                utf8(rows, |o| o.classified_by.as_deref()),
+            )
+            .timestamp("classificationTimestamp", true, rows, |o| {
+                o.classification_timestamp
+            })
+            .column(
+                "classificationProbability",
+                true,
+                float64(rows, |o| o.classification_probability),
+            )
+            .column(
+                "observationTags",
+                true,
+                utf8(rows, |o| o.observation_tags.as_deref()),
+            )
+            .column(
+                "observationComments",
+                true,
+                utf8(rows, |o| o.observation_comments.as_deref()),
+            )
+            .build()
+    }
+
+    fn from_record_batch(batch: &RecordBatch) -> Result<Vec<Self>, ArrowError> {
+        let c = Columns::new(batch)?;
+        (0..c.rows)
+            .map(|i| {
+                Ok(Observation {
+                    observation_id: required("observationID", c.string("observationID", i))?,
+                    deployment_id: required("deploymentID", c.string("deploymentID", i))?,
+                    media_id: c.string("mediaID", i),
+                    event_id: c.string("eventID", i),
+                    event_start: c.timestamp("eventStart", i),
+                    event_end: c.timestamp("eventEnd", i),
+                    observation_level: required(
+                        "observationLevel",
+                        c.enumeration("observationLevel", i),
+                    )?,
+                    observation_type: required(
+                        "observationType",
+                        c.enumeration("observationType", i),
+                    )?,
+                    camera_setup_type: c.enumeration("cameraSetupType", i),
+                    scientific_name: c.string("scientificName", i),
+                    count: c.uint32("count", i),
+                    life_stage: c.enumeration("lifeStage", i),
+                    sex: c.enumeration("sex", i),
+                    behavior: c.string("behavior", i),
+                    individual_id: c.string("individualID", i),
+                    individual_position_radius: c.float64("individualPositionRadius", i),
+                    individual_position_angle: c.float64("individualPositionAngle", i),
+                    individual_speed: c.float64("individualSpeed", i),
+                    bbox_x: c.float64("bboxX", i),
+                    bbox_y: c.float64("bboxY", i),
+                    bbox_width: c.float64("bboxWidth", i),
+                    bbox_height: c.float64("bboxHeight", i),
+                    classification_method: c.enumeration("classificationMethod", i),
+                    classified_by: c.string("classifiedBy", i),
+                    classification_timestamp: c.timestamp("classificationTimestamp", i),
+                    classification_probability: c.float64("classificationProbability", i),
+                    observation_tags: c.string("observationTags", i),
+                    observation_comments: c.string("observationComments", i),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Package;
+
+    #[test]
+    fn record_batch_types() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let batch = Observation::to_record_batch(&package.observations).unwrap();
+        assert_eq!(batch.num_rows(), 549);
+        let schema = batch.schema();
+        assert_eq!(
+            schema
+                .field_with_name("observationType")
+                .unwrap()
+                .data_type(),
+            &DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8))
+        );
+        assert_eq!(
+            schema.field_with_name("eventStart").unwrap().data_type(),
+            &DataType::Timestamp(TimeUnit::Second, Some("UTC".into()))
+        );
+        assert_eq!(
+            schema
+                .field_with_name("eventStartOffset")
+                .unwrap()
+                .data_type(),
+            &DataType::Int16
+        );
+        assert_eq!(Deployment::schema().fields().len(), 26);
+    }
+
+    #[test]
+    fn parquet_round_trip() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        package.normalize_to_timezones(
+            &package
+                .deployments
+                .iter()
+                .map(|d| (d.deployment_id.clone(), chrono_tz::America::St_Johns))
+                .collect(),
+        );
+        let path = |resource: &str| {
+            std::env::temp_dir()
+                .join(format!(
+                    "camtrap_dp_{}_{}.parquet",
+                    resource,
+                    std::process::id()
+                ))
+                .to_string_lossy()
+                .into_owned()
+        };
+        let (deployments, media, observations) =
+            (path("deployments"), path("media"), path("observations"));
+
+        Deployment::to_parquet(&package.deployments, &deployments).unwrap();
+        Medium::to_parquet(&package.media, &media).unwrap();
+        Observation::to_parquet(&package.observations, &observations).unwrap();
+        let deployments_out = Deployment::from_parquet(&deployments).unwrap();
+        let media_out = Medium::from_parquet(&media).unwrap();
+        let observations_out = Observation::from_parquet(&observations).unwrap();
+        for path in [deployments, media, observations] {
+            std::fs::remove_file(path).unwrap();
+        }
+
+        assert_eq!(deployments_out, package.deployments);
+        assert_eq!(media_out, package.media);
+        assert_eq!(observations_out, package.observations);
+        assert_eq!(
+            media_out[0].timestamp.to_rfc3339(),
+            package.media[0].timestamp.to_rfc3339()
+        );
+        assert!(media_out[0].timestamp.to_rfc3339().ends_with("-02:30"));
+        assert_eq!(
+            deployments_out
+                .iter()
+                .map(|d| d.deployment_start.to_rfc3339())
+                .collect::<Vec<_>>(),
+            package
+                .deployments
+                .iter()
+                .map(|d| d.deployment_start.to_rfc3339())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn missing_required_column() {
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new(
+                "mediaID",
+                DataType::Utf8,
+                false,
+            )])),
+            vec![Arc::new(StringArray::from(vec!["a"]))],
+        )
+        .unwrap();
+        assert!(Medium::from_record_batch(&batch).is_err());
+    }
+}
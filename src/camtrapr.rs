@@ -0,0 +1,384 @@
+//! Export to the `recordTable` and camera operation (`camOp`) tables of camtrapR.
+//!
+//! Stations are location names (falling back to the locationID and deploymentID) and cameras
+//! are camera IDs (falling back to the deploymentID). Date and times are written in the local
+//! time of each record, as camtrapR expects.
+
+use crate::effort::EffortOptions;
+use crate::independence::{ComparedTo, GroupBy, IndependenceOptions, TimestampSource};
+use crate::{Deployment, ObservationLevel, Package, WriteDataPackageCsv};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Options for [`Package::record_table`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordTableOptions {
+    /// Options used to select records. The threshold is camtrapR's `minDeltaTime`.
+    pub independence: IndependenceOptions,
+}
+
+impl Default for RecordTableOptions {
+    fn default() -> Self {
+        RecordTableOptions {
+            independence: IndependenceOptions {
+                threshold: Duration::zero(),
+                group_by: GroupBy::Location,
+                timestamp_source: TimestampSource::Media,
+                compared_to: ComparedTo::LastRecord,
+                observation_level: ObservationLevel::Media,
+            },
+        }
+    }
+}
+
+/// Row of a camtrapR `recordTable`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    #[serde(rename = "Station")]
+    pub station: String,
+
+    #[serde(rename = "Camera")]
+    pub camera: String,
+
+    #[serde(rename = "Species")]
+    pub species: String,
+
+    /// Local date and time, as `YYYY-MM-DD HH:MM:SS`.
+    #[serde(rename = "DateTimeOriginal")]
+    pub date_time_original: String,
+
+    #[serde(rename = "Date")]
+    pub date: String,
+
+    #[serde(rename = "Time")]
+    pub time: String,
+
+    /// Time since the previous record of the species at the station (0 for the first one).
+    #[serde(rename = "delta.time.secs")]
+    pub delta_time_secs: i64,
+
+    #[serde(rename = "delta.time.mins")]
+    pub delta_time_mins: f64,
+
+    #[serde(rename = "delta.time.hours")]
+    pub delta_time_hours: f64,
+
+    #[serde(rename = "delta.time.days")]
+    pub delta_time_days: f64,
+
+    /// Directory of the media file of the record.
+    #[serde(rename = "Directory")]
+    pub directory: String,
+
+    /// File name of the media file of the record.
+    #[serde(rename = "FileName")]
+    pub file_name: String,
+}
+
+impl WriteDataPackageCsv<Record> for Vec<Record> {}
+
+/// Options for [`Package::camera_operation`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CameraOperationOptions {
+    /// If true, there is a row per camera at each station (`<station>__CAM_<camera>`, as
+    /// camtrapR's `byCamera`) instead of a row per station.
+    pub by_camera: bool,
+
+    /// Options used to compute active intervals (e.g. malfunctions).
+    pub effort: EffortOptions,
+}
+
+/// Camera operation matrix, as camtrapR's `cameraOperation` with `hasProblems = TRUE`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraOperation {
+    /// Stations (or station and camera), in row order.
+    pub stations: Vec<String>,
+
+    /// Days, in column order.
+    pub dates: Vec<NaiveDate>,
+
+    /// Fraction of each day the cameras were active, summed over the cameras of a station.
+    /// `Some(0.0)` if a camera was set up but not active (malfunction), `None` if no camera
+    /// was set up.
+    pub operation: Vec<Vec<Option<f64>>>,
+}
+
+impl CameraOperation {
+    /// Write the matrix to a CSV file, with stations as rows, days (`YYYY-MM-DD`) as columns
+    /// and `NA` for days without cameras.
+    pub fn to_file(&self, path: &str) -> Result<(), csv::Error> {
+        let mut wtr = csv::Writer::from_path(path)?;
+
+        let mut header = vec!["Station".to_string()];
+        header.extend(self.dates.iter().map(|date| date.to_string()));
+        wtr.write_record(&header)?;
+
+        for (station, row) in self.stations.iter().zip(&self.operation) {
+            let mut record = vec![station.clone()];
+            record.extend(row.iter().map(|value| match value {
+                Some(value) => value.to_string(),
+                None => "NA".to_string(),
+            }));
+            wtr.write_record(&record)?;
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+fn station(deployment: &Deployment) -> String {
+    deployment
+        .location_name
+        .clone()
+        .or_else(|| deployment.location_id.clone())
+        .unwrap_or_else(|| deployment.deployment_id.clone())
+}
+
+fn camera(deployment: &Deployment) -> String {
+    deployment
+        .camera_id
+        .clone()
+        .unwrap_or_else(|| deployment.deployment_id.clone())
+}
+
+fn overlap(
+    (start, end): (DateTime<FixedOffset>, DateTime<FixedOffset>),
+    (from, to): (DateTime<FixedOffset>, DateTime<FixedOffset>),
+) -> Duration {
+    (end.min(to) - start.max(from)).max(Duration::zero())
+}
+
+impl Package {
+    /// camtrapR `recordTable`: a row per record, ordered by station, species and date and
+    /// time.
+    pub fn record_table(&self, options: &RecordTableOptions) -> Vec<Record> {
+        let observations: HashMap<&str, Option<&str>> = self
+            .observations
+            .iter()
+            .map(|o| (o.observation_id.as_str(), o.media_id.as_deref()))
+            .collect();
+
+        let mut records: Vec<(String, String, DateTime<FixedOffset>, Record)> = self
+            .independent_detections(&options.independence)
+            .into_iter()
+            .filter_map(|detection| {
+                let deployment = self.deployment(&detection.deployment_id)?;
+                let medium = observations
+                    .get(detection.observation_id.as_str())
+                    .copied()
+                    .flatten()
+                    .and_then(|media_id| self.medium(media_id));
+                let (directory, file_name) = match medium {
+                    Some(medium) => {
+                        let (directory, name) = medium
+                            .file_path
+                            .rsplit_once('/')
+                            .unwrap_or(("", &medium.file_path));
+                        (
+                            directory.to_string(),
+                            medium.file_name.clone().unwrap_or_else(|| name.to_string()),
+                        )
+                    }
+                    None => (String::new(), String::new()),
+                };
+                let timestamp = detection.timestamp;
+                Some((
+                    station(deployment),
+                    detection.scientific_name.clone(),
+                    timestamp,
+                    Record {
+                        station: station(deployment),
+                        camera: camera(deployment),
+                        species: detection.scientific_name,
+                        date_time_original: timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+                        date: timestamp.format("%Y-%m-%d").to_string(),
+                        time: timestamp.format("%H:%M:%S").to_string(),
+                        delta_time_secs: 0,
+                        delta_time_mins: 0.0,
+                        delta_time_hours: 0.0,
+                        delta_time_days: 0.0,
+                        directory,
+                        file_name,
+                    },
+                ))
+            })
+            .collect();
+        records.sort_by(|a, b| (&a.0, &a.1, a.2).cmp(&(&b.0, &b.1, b.2)));
+
+        let mut previous: Option<(String, String, DateTime<FixedOffset>)> = None;
+        records
+            .into_iter()
+            .map(|(station, species, timestamp, mut record)| {
+                if let Some((_, _, last)) = previous
+                    .as_ref()
+                    .filter(|(s, sp, _)| *s == station && *sp == species)
+                {
+                    let secs = (timestamp - *last).num_seconds();
+                    record.delta_time_secs = secs;
+                    record.delta_time_mins = secs as f64 / 60.0;
+                    record.delta_time_hours = secs as f64 / 3600.0;
+                    record.delta_time_days = secs as f64 / 86400.0;
+                }
+                previous = Some((station, species, timestamp));
+                record
+            })
+            .collect()
+    }
+
+    /// camtrapR camera operation matrix, with a column per day from the first deployment start
+    /// to the last deployment end. Days are local days in the UTC offset of each deployment
+    /// start.
+    pub fn camera_operation(&self, options: &CameraOperationOptions) -> CameraOperation {
+        let row = |deployment: &Deployment| {
+            if options.by_camera {
+                format!("{}__CAM_{}", station(deployment), camera(deployment))
+            } else {
+                station(deployment)
+            }
+        };
+
+        let first = self
+            .deployments
+            .iter()
+            .map(|d| d.deployment_start.date_naive())
+            .min();
+        let last = self
+            .deployments
+            .iter()
+            .map(|d| {
+                self.effective_end(d, &options.effort)
+                    .with_timezone(&d.deployment_start.timezone())
+                    .date_naive()
+            })
+            .max();
+        let dates: Vec<NaiveDate> = match (first, last) {
+            (Some(first), Some(last)) => first.iter_days().take_while(|d| *d <= last).collect(),
+            _ => Vec::new(),
+        };
+
+        let mut rows: BTreeMap<String, Vec<Option<f64>>> = BTreeMap::new();
+        for deployment in &self.deployments {
+            let offset = deployment.deployment_start.timezone();
+            let window = (
+                deployment.deployment_start,
+                self.effective_end(deployment, &options.effort),
+            );
+            let active = self.active_intervals(deployment, &options.effort);
+            let operation = rows
+                .entry(row(deployment))
+                .or_insert_with(|| vec![None; dates.len()]);
+
+            for (date, value) in dates.iter().zip(operation.iter_mut()) {
+                let day_start = offset
+                    .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+                    .unwrap();
+                let day = (day_start, day_start + Duration::days(1));
+                if overlap(window, day) == Duration::zero() {
+                    continue;
+                }
+                let seconds: i64 = active
+                    .iter()
+                    .map(|interval| overlap(*interval, day).num_seconds())
+                    .sum();
+                *value = Some(value.unwrap_or(0.0) + seconds as f64 / 86400.0);
+            }
+        }
+
+        CameraOperation {
+            stations: rows.keys().cloned().collect(),
+            dates,
+            operation: rows.into_values().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::effort::Malfunction;
+    use crate::util::test::temp_path;
+
+    #[test]
+    fn record_table() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let table = package.record_table(&RecordTableOptions::default());
+
+        assert!(!table.is_empty());
+        let first = &table[0];
+        assert_eq!(first.delta_time_secs, 0);
+        assert!(!first.file_name.is_empty());
+        assert!(table.windows(2).all(
+            |pair| (&pair[0].station, &pair[0].species) <= (&pair[1].station, &pair[1].species)
+        ));
+        assert!(table.iter().all(|r| r.delta_time_secs >= 0));
+
+        let mallard = table
+            .iter()
+            .find(|r| r.file_name == "20200709093328-RCNX0001.JPG")
+            .unwrap();
+        assert_eq!(mallard.date_time_original, "2020-05-30 04:57:37");
+        assert_eq!(mallard.station, "B_HS_val 2_processiepark");
+        assert_eq!(mallard.camera, "320");
+
+        let path = temp_path("record_table.csv");
+        table.to_file(&path).unwrap();
+        let header = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(
+            header.starts_with("Station,Camera,Species,DateTimeOriginal,Date,Time,delta.time.secs")
+        );
+    }
+
+    #[test]
+    fn camera_operation() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let deployment = package.deployment("00a2c20d").unwrap();
+        let start = deployment.deployment_start;
+        let operation = package.camera_operation(&CameraOperationOptions {
+            by_camera: true,
+            effort: EffortOptions {
+                malfunctions: vec![Malfunction {
+                    deployment_id: "00a2c20d".to_string(),
+                    start: start + Duration::days(2),
+                    end: start + Duration::days(4),
+                }],
+                ..Default::default()
+            },
+        });
+
+        assert_eq!(operation.stations.len(), 4);
+        let row = operation
+            .stations
+            .iter()
+            .position(|s| s == "B_HS_val 2_processiepark__CAM_320")
+            .unwrap();
+        let column = |date: NaiveDate| operation.dates.iter().position(|d| *d == date).unwrap();
+        let day = start.date_naive();
+        let values = &operation.operation[row];
+        assert!(values[column(day)].unwrap() < 1.0);
+        assert_eq!(values[column(day + Duration::days(3))], Some(0.0));
+        assert!(values.iter().any(|v| v.is_none()));
+        assert!(values.iter().flatten().all(|v| (0.0..=1.0).contains(v)));
+
+        let path = temp_path("camop.csv");
+        operation.to_file(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut lines = content.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(header[0], "Station");
+        assert_eq!(header.len(), operation.dates.len() + 1);
+        assert_eq!(header[1], operation.dates[0].to_string());
+        let cells: Vec<&str> = lines
+            .find(|line| line.starts_with("B_HS_val 2_processiepark__CAM_320,"))
+            .unwrap()
+            .split(',')
+            .collect();
+        assert_eq!(cells[column(day + Duration::days(3)) + 1], "0");
+        for (value, cell) in values.iter().zip(&cells[1..]) {
+            assert_eq!(value.is_none(), *cell == "NA");
+        }
+    }
+}
@@ -0,0 +1,828 @@
+//! COCO Camera Traps datasets, the format used by LILA BC and for training classifiers.
+//!
+//! Media files map to `images`, media-based observations to `annotations` and observed taxa
+//! (or observation types, for observations without a scientific name) to `categories`.
+//! Category `0` is reserved for `empty` (blank) images. Bounding boxes are in pixels
+//! (`[x, y, width, height]` from the top-left corner) in COCO, while they are relative to the
+//! image size in Camtrap DP. See <https://github.com/agentmorris/MegaDetector/blob/main/megadetector/data_management/README.md#coco-camera-traps-format>.
+
+use crate::metadata::{Contributor, PackageMetadata};
+use crate::util::{media_type, name};
+use crate::{
+    ClassificationMethod, Deployment, Medium, Observation, ObservationLevel, ObservationType,
+    Package, PackageError,
+};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Timestamp format of `images.datetime`.
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// COCO Camera Traps dataset.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CocoDataset {
+    /// Information about the dataset.
+    #[serde(default)]
+    pub info: CocoInfo,
+
+    /// Images.
+    pub images: Vec<CocoImage>,
+
+    /// Categories (taxa or observation types).
+    pub categories: Vec<CocoCategory>,
+
+    /// Annotations of the images.
+    pub annotations: Vec<CocoAnnotation>,
+}
+
+/// Information about the dataset.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CocoInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "year"
+    )]
+    pub year: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contributor: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_created: Option<String>,
+}
+
+/// Image (media file).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CocoImage {
+    /// Unique identifier of the image.
+    #[serde(deserialize_with = "required_string_or_number")]
+    pub id: String,
+
+    /// Path of the image file.
+    pub file_name: String,
+
+    /// Width in pixels, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+
+    /// Height in pixels, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+
+    /// Location (camera placement) of the image.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "string_or_number"
+    )]
+    pub location: Option<String>,
+
+    /// Local date and time, as `YYYY-MM-DD HH:MM:SS`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datetime: Option<String>,
+
+    /// Sequence (event) the image belongs to.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "string_or_number"
+    )]
+    pub seq_id: Option<String>,
+
+    /// Number of images in the sequence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq_num_frames: Option<u32>,
+
+    /// Position of the image in the sequence, starting at 0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_num: Option<u32>,
+}
+
+/// Category of annotations.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CocoCategory {
+    pub id: u32,
+    pub name: String,
+}
+
+/// Annotation of an image.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CocoAnnotation {
+    /// Unique identifier of the annotation.
+    #[serde(deserialize_with = "required_string_or_number")]
+    pub id: String,
+
+    /// Identifier of the annotated image.
+    #[serde(deserialize_with = "required_string_or_number")]
+    pub image_id: String,
+
+    /// Identifier of the category.
+    pub category_id: u32,
+
+    /// Bounding box in pixels, as `[x, y, width, height]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<[f64; 4]>,
+
+    /// true if the annotation applies to the whole sequence rather than this image only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence_level_annotation: Option<bool>,
+}
+
+/// Identifiers are strings in most datasets but integers in some.
+fn string_or_number<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::String(s)) => Some(s),
+            Some(serde_json::Value::Null) | None => None,
+            Some(value) => Some(value.to_string()),
+        },
+    )
+}
+
+fn required_string_or_number<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    string_or_number(deserializer)?.ok_or_else(|| serde::de::Error::custom("missing identifier"))
+}
+
+/// Years are integers in most datasets but strings, possibly empty, in some.
+fn year<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<i32>, D::Error> {
+    string_or_number(deserializer)?
+        .filter(|year| !year.trim().is_empty())
+        .map(|year| year.trim().parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+impl CocoDataset {
+    /// Read a COCO Camera Traps dataset from a JSON file.
+    pub fn from_file(path: &str) -> Result<Self, PackageError> {
+        let file = std::fs::File::open(path).map_err(PackageError::Io)?;
+        serde_json::from_reader(std::io::BufReader::new(file)).map_err(PackageError::Json)
+    }
+
+    /// Write the dataset to a JSON file.
+    pub fn to_file(&self, path: &str) -> Result<(), PackageError> {
+        let file = std::fs::File::create(path).map_err(PackageError::Io)?;
+        serde_json::to_writer_pretty(file, self).map_err(PackageError::Json)
+    }
+}
+
+/// Image size in pixels from the EXIF data of a media file.
+pub fn image_size(exif_data: &serde_json::Value) -> Option<(u32, u32)> {
+    let dimension = |keys: &[&str]| {
+        keys.iter().find_map(|key| {
+            let value = exif_data.get(key)?;
+            value
+                .as_u64()
+                .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+                .map(|v| v as u32)
+        })
+    };
+    Some((
+        dimension(&["ImageWidth", "ExifImageWidth", "PixelXDimension", "width"])?,
+        dimension(&[
+            "ImageHeight",
+            "ExifImageHeight",
+            "PixelYDimension",
+            "height",
+        ])?,
+    ))
+}
+
+/// Category name of an observation: its scientific name, or its observation type.
+fn category_name(observation: &Observation) -> String {
+    match (&observation.scientific_name, &observation.observation_type) {
+        (_, ObservationType::Blank) => "empty".to_string(),
+        (Some(scientific_name), _) => scientific_name.clone(),
+        (None, observation_type) => name(observation_type),
+    }
+}
+
+impl Package {
+    /// COCO Camera Traps dataset of the image media files and their media-based observations.
+    /// Bounding boxes are converted to pixels for media files whose EXIF data contains the
+    /// image size, and omitted otherwise. Sequences are the events of the media-based
+    /// observations.
+    pub fn to_coco(&self) -> CocoDataset {
+        let observations: Vec<&Observation> = self
+            .observations
+            .iter()
+            .filter(|o| o.observation_level == ObservationLevel::Media && o.media_id.is_some())
+            .collect();
+
+        let names: BTreeSet<String> = observations
+            .iter()
+            .map(|o| category_name(o))
+            .filter(|name| name != "empty")
+            .collect();
+        let mut categories = vec![CocoCategory {
+            id: 0,
+            name: "empty".to_string(),
+        }];
+        categories.extend(names.into_iter().enumerate().map(|(i, name)| CocoCategory {
+            id: i as u32 + 1,
+            name,
+        }));
+        let category_ids: HashMap<&str, u32> = categories
+            .iter()
+            .map(|category| (category.name.as_str(), category.id))
+            .collect();
+
+        let mut sequences: HashMap<&str, &str> = HashMap::new();
+        for observation in &observations {
+            if let (Some(media_id), Some(event_id)) = (&observation.media_id, &observation.event_id)
+            {
+                sequences.entry(media_id).or_insert(event_id);
+            }
+        }
+
+        let mut media: Vec<&Medium> = self
+            .media
+            .iter()
+            .filter(|m| m.file_mediatype.starts_with("image/"))
+            .collect();
+        media.sort_by_key(|m| (m.deployment_id.clone(), m.timestamp));
+        let mut frames: HashMap<&str, Vec<&str>> = HashMap::new();
+        for medium in &media {
+            if let Some(seq_id) = sequences.get(medium.media_id.as_str()) {
+                frames.entry(seq_id).or_default().push(&medium.media_id);
+            }
+        }
+
+        let mut sizes: HashMap<&str, (u32, u32)> = HashMap::new();
+        let images = media
+            .iter()
+            .map(|medium| {
+                let size = medium.exif_data.as_ref().and_then(image_size);
+                if let Some(size) = size {
+                    sizes.insert(&medium.media_id, size);
+                }
+                let seq_id = sequences.get(medium.media_id.as_str()).copied();
+                let sequence = seq_id.and_then(|seq_id| frames.get(seq_id));
+                CocoImage {
+                    id: medium.media_id.clone(),
+                    file_name: medium.file_path.clone(),
+                    width: size.map(|(width, _)| width),
+                    height: size.map(|(_, height)| height),
+                    location: self.deployment(&medium.deployment_id).map(|deployment| {
+                        deployment
+                            .location_id
+                            .clone()
+                            .unwrap_or_else(|| deployment.deployment_id.clone())
+                    }),
+                    datetime: Some(medium.timestamp.format(DATETIME_FORMAT).to_string()),
+                    seq_id: seq_id.map(|seq_id| seq_id.to_string()),
+                    seq_num_frames: sequence.map(|frames| frames.len() as u32),
+                    frame_num: sequence.and_then(|frames| {
+                        frames
+                            .iter()
+                            .position(|id| *id == medium.media_id)
+                            .map(|n| n as u32)
+                    }),
+                }
+            })
+            .collect();
+
+        let exported: HashSet<&str> = media.iter().map(|m| m.media_id.as_str()).collect();
+        let annotations = observations
+            .iter()
+            .filter(|o| {
+                o.media_id
+                    .as_deref()
+                    .is_some_and(|id| exported.contains(id))
+            })
+            .map(|observation| {
+                let media_id = observation.media_id.clone().unwrap_or_default();
+                let bbox = match (
+                    sizes.get(media_id.as_str()),
+                    observation.bbox_x,
+                    observation.bbox_y,
+                    observation.bbox_width,
+                    observation.bbox_height,
+                ) {
+                    (Some((width, height)), Some(x), Some(y), Some(w), Some(h)) => {
+                        let (width, height) = (*width as f64, *height as f64);
+                        Some([x * width, y * height, w * width, h * height])
+                    }
+                    _ => None,
+                };
+                CocoAnnotation {
+                    id: observation.observation_id.clone(),
+                    image_id: media_id,
+                    category_id: category_ids[category_name(observation).as_str()],
+                    bbox,
+                    sequence_level_annotation: Some(false),
+                }
+            })
+            .collect();
+
+        let metadata = self.metadata.clone().unwrap_or_default();
+        CocoDataset {
+            info: CocoInfo {
+                version: metadata.version,
+                description: metadata.title,
+                year: None,
+                contributor: metadata
+                    .contributors
+                    .first()
+                    .map(|contributor| contributor.title.clone()),
+                date_created: metadata.created,
+            },
+            images,
+            categories,
+            annotations,
+        }
+    }
+}
+
+/// Options for [`Package::from_coco`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CocoImportOptions {
+    /// UTC offset of the image date and times, which COCO Camera Traps stores without one.
+    pub utc_offset: FixedOffset,
+
+    /// Images at the same location are split into separate deployments when they are further
+    /// apart than this.
+    pub deployment_gap: Duration,
+
+    /// Value of `filePublic` for the media files.
+    pub file_public: bool,
+
+    /// Scientific names of the category names, which are mostly common names in camera trap
+    /// datasets (e.g. `deer` → `Capreolus capreolus`). Category names not in the lookup are
+    /// used as they are.
+    pub species_lookup: HashMap<String, String>,
+}
+
+impl Default for CocoImportOptions {
+    fn default() -> Self {
+        CocoImportOptions {
+            utc_offset: FixedOffset::east_opt(0).unwrap(),
+            deployment_gap: Duration::days(30),
+            file_public: true,
+            species_lookup: HashMap::new(),
+        }
+    }
+}
+
+/// Outcome of [`Package::from_coco`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CocoImport {
+    /// Imported package.
+    pub package: Package,
+
+    /// Images without a (parsable) date and time, which are not imported, nor are their
+    /// annotations.
+    pub skipped: Vec<String>,
+}
+
+fn parse_datetime(datetime: &str, offset: &FixedOffset) -> Option<DateTime<FixedOffset>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(datetime) {
+        return Some(timestamp);
+    }
+    [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y:%m:%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y/%m/%d %H:%M:%S",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(datetime.trim(), format).ok())
+    .and_then(|naive| offset.from_local_datetime(&naive).single())
+}
+
+fn observation_type(category: &str) -> ObservationType {
+    match category.to_lowercase().as_str() {
+        "empty" | "blank" => ObservationType::Blank,
+        "human" | "person" | "people" => ObservationType::Human,
+        "vehicle" | "car" => ObservationType::Vehicle,
+        "unknown" | "unidentifiable" | "unidentified" => ObservationType::Unknown,
+        _ => ObservationType::Animal,
+    }
+}
+
+impl Package {
+    /// Package from a COCO Camera Traps dataset. Deployments are synthesised per location:
+    /// consecutive images at a location belong to the same deployment unless they are more
+    /// than the deployment gap apart. Deployment IDs are `<location>_<YYYYMMDD>` of the first
+    /// image, followed by `_<n>` for the n-th deployment starting that day at that location. Sequences become events and annotations become media-based observations,
+    /// classified by a human, with bounding boxes made relative to the image size when it is
+    /// known. The scientific name of an animal observation is looked up from its category
+    /// name in `species_lookup`, falling back to the category name itself.
+    pub fn from_coco(dataset: &CocoDataset, options: &CocoImportOptions) -> CocoImport {
+        let mut import = CocoImport::default();
+
+        let mut by_location: BTreeMap<String, Vec<(&CocoImage, DateTime<FixedOffset>)>> =
+            BTreeMap::new();
+        for image in &dataset.images {
+            match image
+                .datetime
+                .as_deref()
+                .and_then(|datetime| parse_datetime(datetime, &options.utc_offset))
+            {
+                Some(timestamp) => by_location
+                    .entry(
+                        image
+                            .location
+                            .clone()
+                            .unwrap_or_else(|| "unknown".to_string()),
+                    )
+                    .or_default()
+                    .push((image, timestamp)),
+                None => import.skipped.push(image.id.clone()),
+            }
+        }
+
+        let package = &mut import.package;
+        let mut images: HashMap<&str, (&CocoImage, DateTime<FixedOffset>, String)> = HashMap::new();
+        let mut deployment_ids: HashMap<String, usize> = HashMap::new();
+        for (location, mut located) in by_location {
+            located.sort_by_key(|(image, timestamp)| (*timestamp, image.id.clone()));
+            let mut groups: Vec<Vec<(&CocoImage, DateTime<FixedOffset>)>> = Vec::new();
+            for (image, timestamp) in located {
+                match groups.last_mut() {
+                    Some(group)
+                        if timestamp - group.last().unwrap().1 <= options.deployment_gap =>
+                    {
+                        group.push((image, timestamp))
+                    }
+                    _ => groups.push(vec![(image, timestamp)]),
+                }
+            }
+
+            for group in groups {
+                let (start, end) = (group[0].1, group[group.len() - 1].1);
+                let mut deployment_id = format!("{}_{}", location, start.format("%Y%m%d"));
+                let n = deployment_ids.entry(deployment_id.clone()).or_insert(0);
+                *n += 1;
+                if *n > 1 {
+                    deployment_id = format!("{}_{}", deployment_id, n);
+                }
+                package.deployments.push(Deployment {
+                    deployment_id: deployment_id.clone(),
+                    location_id: Some(location.clone()),
+                    location_name: Some(location.clone()),
+                    latitude: None,
+                    longitude: None,
+                    location_radius: None,
+                    deployment_start: start,
+                    deployment_end: end,
+                    setup_by: None,
+                    camera_id: None,
+                    camera_model: None,
+                    camera_delay: None,
+                    camera_height: None,
+                    camera_depth: None,
+                    camera_angle: None,
+                    camera_heading: None,
+                    detection_distance: None,
+                    timestamp_issues: None,
+                    bait_use: None,
+                    feature_type: None,
+                    habitat: None,
+                    deployment_groups: None,
+                    tags: None,
+                    comments: None,
+                });
+
+                for (image, timestamp) in group {
+                    package.media.push(Medium {
+                        media_id: image.id.clone(),
+                        deployment_id: deployment_id.clone(),
+                        capture_method: None,
+                        timestamp,
+                        file_path: image.file_name.clone(),
+                        file_public: options.file_public,
+                        file_name: image
+                            .file_name
+                            .rsplit(['/', '\\'])
+                            .next()
+                            .map(|name| name.to_string()),
+                        file_mediatype: media_type(&image.file_name).to_string(),
+                        exif_data: match (image.width, image.height) {
+                            (Some(width), Some(height)) => Some(serde_json::json!({
+                                "ImageWidth": width,
+                                "ImageHeight": height
+                            })),
+                            _ => None,
+                        },
+                        favorite: None,
+                        comments: None,
+                    });
+                    images.insert(&image.id, (image, timestamp, deployment_id.clone()));
+                }
+            }
+        }
+
+        let categories: HashMap<u32, &str> = dataset
+            .categories
+            .iter()
+            .map(|category| (category.id, category.name.as_str()))
+            .collect();
+        for annotation in &dataset.annotations {
+            let (image, timestamp, deployment_id) = match images.get(annotation.image_id.as_str()) {
+                Some(image) => image,
+                None => continue,
+            };
+            let category = categories
+                .get(&annotation.category_id)
+                .copied()
+                .unwrap_or("unknown");
+            let observation_type = observation_type(category);
+            let bbox = match (annotation.bbox, image.width, image.height) {
+                (Some([x, y, w, h]), Some(width), Some(height)) if width > 0 && height > 0 => {
+                    let (width, height) = (width as f64, height as f64);
+                    Some([x / width, y / height, w / width, h / height])
+                }
+                _ => None,
+            };
+            package.observations.push(Observation {
+                observation_id: annotation.id.clone(),
+                deployment_id: deployment_id.clone(),
+                media_id: Some(image.id.clone()),
+                event_id: image.seq_id.clone(),
+                event_start: Some(*timestamp),
+                event_end: Some(*timestamp),
+                observation_level: ObservationLevel::Media,
+                scientific_name: (observation_type == ObservationType::Animal).then(|| {
+                    options
+                        .species_lookup
+                        .get(category)
+                        .cloned()
+                        .unwrap_or_else(|| category.to_string())
+                }),
+                observation_type,
+                camera_setup_type: None,
+                count: annotation.bbox.map(|_| 1),
+                life_stage: None,
+                sex: None,
+                behavior: None,
+                individual_id: None,
+                individual_position_radius: None,
+                individual_position_angle: None,
+                individual_speed: None,
+                bbox_x: bbox.map(|b| b[0]),
+                bbox_y: bbox.map(|b| b[1]),
+                bbox_width: bbox.map(|b| b[2]),
+                bbox_height: bbox.map(|b| b[3]),
+                classification_method: Some(ClassificationMethod::Human),
+                classified_by: None,
+                classification_timestamp: None,
+                classification_probability: None,
+                observation_tags: None,
+                observation_comments: None,
+            });
+        }
+
+        package.metadata = Some(PackageMetadata {
+            title: dataset.info.description.clone(),
+            version: dataset.info.version.clone(),
+            created: dataset.info.date_created.clone(),
+            contributors: dataset
+                .info
+                .contributor
+                .iter()
+                .map(|contributor| Contributor {
+                    title: contributor.clone(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        });
+
+        import
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::test::temp_path;
+
+    #[test]
+    fn coco_export() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let medium = package
+            .media
+            .iter_mut()
+            .find(|m| m.media_id == "07840dcc")
+            .unwrap();
+        medium.exif_data = Some(serde_json::json!({"ImageWidth": 1920, "ImageHeight": "1080"}));
+        let observation = package
+            .observations
+            .iter_mut()
+            .find(|o| o.observation_id == "07840dcc_1")
+            .unwrap();
+        observation.bbox_x = Some(0.5);
+        observation.bbox_y = Some(0.25);
+        observation.bbox_width = Some(0.1);
+        observation.bbox_height = Some(0.5);
+
+        let coco = package.to_coco();
+        assert_eq!(coco.images.len(), 423);
+        assert_eq!(coco.annotations.len(), 513);
+        assert_eq!(coco.categories[0].name, "empty");
+
+        let image = coco.images.iter().find(|i| i.id == "07840dcc").unwrap();
+        assert_eq!((image.width, image.height), (Some(1920), Some(1080)));
+        assert_eq!(image.datetime.as_deref(), Some("2020-05-30 04:57:37"));
+        assert_eq!(image.seq_id.as_deref(), Some("4bb69c45"));
+        assert_eq!(image.frame_num, Some(0));
+
+        let annotation = coco
+            .annotations
+            .iter()
+            .find(|a| a.id == "07840dcc_1")
+            .unwrap();
+        assert_eq!(annotation.bbox, Some([960.0, 270.0, 192.0, 540.0]));
+        let category = &coco.categories[annotation.category_id as usize];
+        assert_eq!(category.name, "Anas platyrhynchos");
+
+        assert!(coco
+            .annotations
+            .iter()
+            .filter(|a| a.id != "07840dcc_1")
+            .all(|a| a.bbox.is_none()));
+
+        let path = temp_path("coco.json");
+        coco.to_file(&path).unwrap();
+        let coco_out = CocoDataset::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(coco_out, coco);
+    }
+
+    #[test]
+    fn coco_round_trip() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        for medium in package.media.iter_mut() {
+            medium.exif_data = Some(serde_json::json!({"ImageWidth": 2000, "ImageHeight": 1000}));
+        }
+        let original = package
+            .observations
+            .iter_mut()
+            .find(|o| o.observation_id == "07840dcc_1")
+            .unwrap();
+        original.bbox_x = Some(0.5);
+        original.bbox_y = Some(0.25);
+        original.bbox_width = Some(0.1);
+        original.bbox_height = Some(0.5);
+        let original = original.clone();
+
+        let import = Package::from_coco(&package.to_coco(), &CocoImportOptions::default());
+        assert!(import.skipped.is_empty());
+        let imported = import.package;
+        assert_eq!(imported.media.len(), 423);
+        assert_eq!(imported.observations.len(), 513);
+        assert!(imported
+            .media
+            .iter()
+            .all(|m| imported.deployment(&m.deployment_id).is_some()));
+
+        let observation = imported
+            .observations
+            .iter()
+            .find(|o| o.observation_id == "07840dcc_1")
+            .unwrap();
+        assert_eq!(observation.bbox_x, Some(0.5));
+        assert_eq!(observation.bbox_height, Some(0.5));
+        assert_eq!(observation.event_id, original.event_id);
+        assert_eq!(
+            observation.scientific_name.as_deref(),
+            Some("Anas platyrhynchos")
+        );
+    }
+
+    #[test]
+    fn coco_lila_dataset() {
+        let dataset: CocoDataset = serde_json::from_str(
+            r#"{
+                "info": {"version": "1.0", "description": "Example LILA dataset", "year": 2019},
+                "images": [
+                    {"id": "a/1.JPG", "file_name": "a/1.JPG", "width": 100, "height": 50, "location": 12, "datetime": "2019:06:01 10:00:00", "seq_id": "s1", "seq_num_frames": 2, "frame_num": 0},
+                    {"id": "a/2.JPG", "file_name": "a/2.JPG", "location": 12, "datetime": "2019-06-01 10:00:01", "seq_id": "s1", "seq_num_frames": 2, "frame_num": 1},
+                    {"id": "a/3.JPG", "file_name": "a/3.JPG", "location": 12, "datetime": "2019-09-01 10:00:00"},
+                    {"id": "b/1.JPG", "file_name": "b/1.JPG", "location": 13}
+                ],
+                "categories": [{"id": 0, "name": "empty"}, {"id": 1, "name": "deer"}],
+                "annotations": [
+                    {"id": 1, "image_id": "a/1.JPG", "category_id": 1, "bbox": [10, 10, 50, 25]},
+                    {"id": 2, "image_id": "a/3.JPG", "category_id": 0},
+                    {"id": 3, "image_id": "b/1.JPG", "category_id": 1}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let import = Package::from_coco(
+            &dataset,
+            &CocoImportOptions {
+                species_lookup: HashMap::from([(
+                    "deer".to_string(),
+                    "Capreolus capreolus".to_string(),
+                )]),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(import.skipped, vec!["b/1.JPG"]);
+        let package = import.package;
+        let ids: Vec<&str> = package
+            .deployments
+            .iter()
+            .map(|d| d.deployment_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["12_20190601", "12_20190901"]);
+        assert_eq!(package.observations.len(), 2);
+        assert_eq!(package.observations[0].bbox_width, Some(0.5));
+        assert_eq!(package.observations[0].event_id.as_deref(), Some("s1"));
+        assert_eq!(
+            package.observations[0].scientific_name.as_deref(),
+            Some("Capreolus capreolus")
+        );
+        assert_eq!(
+            package.observations[1].observation_type,
+            ObservationType::Blank
+        );
+        assert_eq!(
+            package.medium("a/1.JPG").unwrap().file_name.as_deref(),
+            Some("1.JPG")
+        );
+    }
+
+    #[test]
+    fn coco_numeric_image_ids() {
+        let dataset: CocoDataset = serde_json::from_str(
+            r#"{
+                "info": {"year": "2019"},
+                "images": [
+                    {"id": 17, "file_name": "a/1.JPG", "location": "a", "datetime": "2019-06-01 10:00:00"},
+                    {"id": 18, "file_name": "a/2.JPG", "location": "a", "datetime": "2019-06-01 10:05:00"}
+                ],
+                "categories": [{"id": 0, "name": "empty"}, {"id": 1, "name": "deer"}],
+                "annotations": [
+                    {"id": 1, "image_id": 18, "category_id": 1},
+                    {"id": 2, "image_id": 17, "category_id": 0}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(dataset.info.year, Some(2019));
+        assert_eq!(dataset.images[0].id, "17");
+
+        let package = Package::from_coco(&dataset, &CocoImportOptions::default()).package;
+        let medium = |observation_id: &str| {
+            let observation = package
+                .observations
+                .iter()
+                .find(|o| o.observation_id == observation_id)
+                .unwrap();
+            package
+                .medium(observation.media_id.as_deref().unwrap())
+                .unwrap()
+        };
+        assert_eq!(medium("1").file_name.as_deref(), Some("2.JPG"));
+        assert_eq!(medium("2").file_name.as_deref(), Some("1.JPG"));
+    }
+
+    #[test]
+    fn coco_deployments_on_the_same_day() {
+        let dataset: CocoDataset = serde_json::from_str(
+            r#"{
+                "info": {"year": ""},
+                "images": [
+                    {"id": "1", "file_name": "1.JPG", "location": "a", "datetime": "2019-06-01 08:00:00"},
+                    {"id": "2", "file_name": "2.JPG", "location": "a", "datetime": "2019-06-01 12:00:00"},
+                    {"id": "3", "file_name": "3.JPG", "location": "a", "datetime": "2019-06-01 16:00:00"}
+                ],
+                "categories": [{"id": 0, "name": "empty"}],
+                "annotations": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(dataset.info.year, None);
+
+        let package = Package::from_coco(
+            &dataset,
+            &CocoImportOptions {
+                deployment_gap: Duration::hours(1),
+                ..Default::default()
+            },
+        )
+        .package;
+        let ids: Vec<&str> = package
+            .deployments
+            .iter()
+            .map(|d| d.deployment_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["a_20190601", "a_20190601_2", "a_20190601_3"]);
+    }
+}
@@ -0,0 +1,322 @@
+//! Derivation of event-level observations from media-level classifications.
+//!
+//! Event-level observations are expected to be mutually exclusive, so their count can be
+//! summed. Media-level observations (e.g. produced by an AI pipeline classifying every image)
+//! are collapsed into one event-level observation per event, observation type, taxon, life
+//! stage and sex.
+
+use crate::tags::{format_tags, Tag};
+use crate::{LifeStage, Observation, ObservationLevel, ObservationType, Sex};
+use std::collections::HashMap;
+
+/// Rule used to derive the count of an event-level observation from the counts of its media.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountRule {
+    /// Highest number of individuals seen in a single media file.
+    MaxPerMedium,
+
+    /// Most frequent number of individuals per media file. Ties are resolved to the highest
+    /// count.
+    Mode,
+}
+
+/// Method used to aggregate the classification probabilities of the media-level observations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbabilityAggregation {
+    /// Highest probability.
+    Max,
+
+    /// Mean probability.
+    Mean,
+
+    /// Lowest probability.
+    Min,
+}
+
+/// Options for [`consolidate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsolidateOptions {
+    /// Rule used to derive the count.
+    pub count_rule: CountRule,
+
+    /// Method used to aggregate classification probabilities.
+    pub probability_aggregation: ProbabilityAggregation,
+}
+
+impl Default for ConsolidateOptions {
+    fn default() -> Self {
+        ConsolidateOptions {
+            count_rule: CountRule::MaxPerMedium,
+            probability_aggregation: ProbabilityAggregation::Max,
+        }
+    }
+}
+
+type GroupKey<'a> = (
+    &'a str,
+    &'a ObservationType,
+    Option<&'a str>,
+    Option<&'a LifeStage>,
+    Option<&'a Sex>,
+);
+
+/// Collapse the media-level observations of each event into event-level observations, one per
+/// observation type, scientific name, life stage and sex.
+///
+/// Media-level observations without an `eventID` are ignored; use
+/// [`Package::assign_events`](crate::Package::assign_events) to reconstruct events first.
+/// Observations are identified as `<eventID>_<n>`, numbered from 1 within each event.
+pub fn consolidate(observations: &[Observation], options: &ConsolidateOptions) -> Vec<Observation> {
+    let mut index: HashMap<GroupKey, usize> = HashMap::new();
+    let mut groups: Vec<(GroupKey, Vec<&Observation>)> = Vec::new();
+    for observation in observations
+        .iter()
+        .filter(|o| o.observation_level == ObservationLevel::Media)
+    {
+        let event_id = match &observation.event_id {
+            Some(event_id) => event_id,
+            None => continue,
+        };
+        let key = (
+            event_id.as_str(),
+            &observation.observation_type,
+            observation.scientific_name.as_deref(),
+            observation.life_stage.as_ref(),
+            observation.sex.as_ref(),
+        );
+        match index.get(&key) {
+            Some(i) => groups[*i].1.push(observation),
+            None => {
+                index.insert(key, groups.len());
+                groups.push((key, vec![observation]));
+            }
+        }
+    }
+
+    let mut per_event: HashMap<&str, usize> = HashMap::new();
+    let mut consolidated: Vec<Observation> = Vec::with_capacity(groups.len());
+    for ((event_id, _, _, _, _), members) in groups {
+        let n = per_event.entry(event_id).or_insert(0);
+        *n += 1;
+        consolidated.push(consolidate_group(
+            format!("{}_{}", event_id, n),
+            &members,
+            options,
+        ));
+    }
+
+    consolidated
+}
+
+fn consolidate_group(
+    observation_id: String,
+    members: &[&Observation],
+    options: &ConsolidateOptions,
+) -> Observation {
+    let first = members[0];
+
+    Observation {
+        observation_id,
+        deployment_id: first.deployment_id.clone(),
+        media_id: None,
+        event_id: first.event_id.clone(),
+        event_start: members.iter().filter_map(|o| o.event_start).min(),
+        event_end: members.iter().filter_map(|o| o.event_end).max(),
+        observation_level: ObservationLevel::Event,
+        observation_type: first.observation_type.clone(),
+        camera_setup_type: members.iter().find_map(|o| o.camera_setup_type.clone()),
+        scientific_name: first.scientific_name.clone(),
+        count: count(members, options.count_rule),
+        life_stage: first.life_stage.clone(),
+        sex: first.sex.clone(),
+        behavior: format_tags(&ranked_tags(members.iter().map(|o| o.behaviors()))),
+        individual_id: None,
+        individual_position_radius: None,
+        individual_position_angle: None,
+        individual_speed: None,
+        bbox_x: None,
+        bbox_y: None,
+        bbox_width: None,
+        bbox_height: None,
+        classification_method: shared(members, |o| o.classification_method.clone()),
+        classified_by: shared(members, |o| o.classified_by.clone()),
+        classification_timestamp: members
+            .iter()
+            .filter_map(|o| o.classification_timestamp)
+            .max(),
+        classification_probability: probability(members, options.probability_aggregation),
+        observation_tags: format_tags(&ranked_tags(
+            members.iter().map(|o| o.parsed_observation_tags()),
+        )),
+        observation_comments: None,
+    }
+}
+
+/// Count per media file (summing observations of the same media file, e.g. one per bounding
+/// box), reduced with the count rule. `None` if none of the observations has a count.
+fn count(members: &[&Observation], rule: CountRule) -> Option<u32> {
+    if members.iter().all(|o| o.count.is_none()) {
+        return None;
+    }
+
+    let mut per_medium: Vec<(Option<&str>, u32)> = Vec::new();
+    for observation in members {
+        let media_id = observation.media_id.as_deref();
+        let count = observation.count.unwrap_or(1);
+        match per_medium.iter_mut().find(|(m, _)| *m == media_id) {
+            Some((_, total)) => *total += count,
+            None => per_medium.push((media_id, count)),
+        }
+    }
+
+    match rule {
+        CountRule::MaxPerMedium => per_medium.iter().map(|(_, count)| *count).max(),
+        CountRule::Mode => {
+            let mut frequencies: Vec<(u32, usize)> = Vec::new();
+            for (_, count) in &per_medium {
+                match frequencies.iter_mut().find(|(c, _)| c == count) {
+                    Some((_, frequency)) => *frequency += 1,
+                    None => frequencies.push((*count, 1)),
+                }
+            }
+            frequencies
+                .into_iter()
+                .max_by_key(|(count, frequency)| (*frequency, *count))
+                .map(|(count, _)| count)
+        }
+    }
+}
+
+fn probability(members: &[&Observation], aggregation: ProbabilityAggregation) -> Option<f64> {
+    let probabilities: Vec<f64> = members
+        .iter()
+        .filter_map(|o| o.classification_probability)
+        .collect();
+    if probabilities.is_empty() {
+        return None;
+    }
+
+    Some(match aggregation {
+        ProbabilityAggregation::Max => probabilities.iter().cloned().fold(f64::MIN, f64::max),
+        ProbabilityAggregation::Min => probabilities.iter().cloned().fold(f64::MAX, f64::min),
+        ProbabilityAggregation::Mean => {
+            probabilities.iter().sum::<f64>() / probabilities.len() as f64
+        }
+    })
+}
+
+/// Value shared by all observations, or `None` if they differ.
+fn shared<T: PartialEq>(
+    members: &[&Observation],
+    f: impl Fn(&Observation) -> Option<T>,
+) -> Option<T> {
+    let value = f(members[0]);
+    if members[1..].iter().all(|o| f(o) == value) {
+        value
+    } else {
+        None
+    }
+}
+
+/// Distinct tags ordered by how often they occur, ties keeping their first appearance.
+fn ranked_tags(lists: impl Iterator<Item = Vec<Tag>>) -> Vec<Tag> {
+    let mut frequencies: Vec<(Tag, usize)> = Vec::new();
+    for tag in lists.flatten() {
+        match frequencies.iter_mut().find(|(t, _)| *t == tag) {
+            Some((_, frequency)) => *frequency += 1,
+            None => frequencies.push((tag, 1)),
+        }
+    }
+    frequencies.sort_by_key(|(_, frequency)| std::cmp::Reverse(*frequency));
+    frequencies.into_iter().map(|(tag, _)| tag).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ReadDataPackageCsv;
+
+    fn media_observation(media_id: &str, count: u32, probability: f64) -> Observation {
+        let timestamp = chrono::DateTime::parse_from_rfc3339("2020-05-30T02:57:37Z").unwrap();
+        Observation {
+            observation_id: format!("{}_1", media_id),
+            deployment_id: "d1".to_string(),
+            media_id: Some(media_id.to_string()),
+            event_id: Some("e1".to_string()),
+            event_start: Some(timestamp),
+            event_end: Some(timestamp),
+            observation_level: ObservationLevel::Media,
+            observation_type: ObservationType::Animal,
+            camera_setup_type: None,
+            scientific_name: Some("Vulpes vulpes".to_string()),
+            count: Some(count),
+            life_stage: None,
+            sex: None,
+            behavior: None,
+            individual_id: None,
+            individual_position_radius: None,
+            individual_position_angle: None,
+            individual_speed: None,
+            bbox_x: None,
+            bbox_y: None,
+            bbox_width: None,
+            bbox_height: None,
+            classification_method: None,
+            classified_by: Some("model".to_string()),
+            classification_timestamp: None,
+            classification_probability: Some(probability),
+            observation_tags: None,
+            observation_comments: None,
+        }
+    }
+
+    #[test]
+    fn count_rules() {
+        let observations = vec![
+            media_observation("m1", 1, 0.6),
+            media_observation("m2", 1, 0.8),
+            media_observation("m3", 1, 0.7),
+            media_observation("m3", 2, 0.9),
+        ];
+
+        let max = consolidate(&observations, &ConsolidateOptions::default());
+        assert_eq!(max.len(), 1);
+        assert_eq!(max[0].observation_id, "e1_1");
+        assert_eq!(max[0].observation_level, ObservationLevel::Event);
+        assert_eq!(max[0].media_id, None);
+        assert_eq!(max[0].count, Some(3));
+        assert_eq!(max[0].classification_probability, Some(0.9));
+        assert_eq!(max[0].classified_by.as_deref(), Some("model"));
+
+        let mode = consolidate(
+            &observations,
+            &ConsolidateOptions {
+                count_rule: CountRule::Mode,
+                probability_aggregation: ProbabilityAggregation::Mean,
+            },
+        );
+        assert_eq!(mode[0].count, Some(1));
+        assert!((mode[0].classification_probability.unwrap() - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn consolidate_fixture_observations() {
+        let observations = Observation::from_file("fixtures/observations.csv").unwrap();
+        let consolidated = consolidate(&observations, &ConsolidateOptions::default());
+        assert!(!consolidated.is_empty());
+
+        let media_events = observations
+            .iter()
+            .filter(|o| o.observation_level == ObservationLevel::Media)
+            .filter_map(|o| o.event_id.as_ref())
+            .collect::<std::collections::HashSet<_>>();
+        let consolidated_events = consolidated
+            .iter()
+            .filter_map(|o| o.event_id.as_ref())
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(media_events, consolidated_events);
+        assert!(consolidated
+            .iter()
+            .all(|o| o.observation_level == ObservationLevel::Event));
+    }
+}
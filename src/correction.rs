@@ -0,0 +1,207 @@
+//! Correction of camera clock errors per deployment.
+//!
+//! Timestamps of a deployment can be shifted by a fixed offset (e.g. a camera set to the
+//! wrong year), or corrected for linear clock drift computed from two reference points for
+//! which both the recorded and the true time are known (e.g. setup and pickup photos).
+
+use crate::Package;
+use chrono::{DateTime, Duration, FixedOffset};
+
+/// Pair of the time recorded by the camera and the true time at that moment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferencePoint {
+    /// Time recorded by the camera.
+    pub recorded: DateTime<FixedOffset>,
+
+    /// True time.
+    pub actual: DateTime<FixedOffset>,
+}
+
+/// Correction of the timestamps of a deployment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClockCorrection {
+    /// Shift all timestamps by a fixed offset.
+    Offset(Duration),
+
+    /// Correct linear clock drift between two reference points. The offset is interpolated
+    /// (and extrapolated) linearly in recorded time.
+    Drift(ReferencePoint, ReferencePoint),
+}
+
+/// Error returned when a correction cannot be applied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CorrectionError {
+    /// No deployment with the given identifier.
+    UnknownDeployment(String),
+
+    /// Both reference points have the same recorded time, so no drift can be computed.
+    InvalidReferencePoints,
+}
+
+impl ClockCorrection {
+    /// Corrected timestamp. The timezone of the timestamp is kept.
+    pub fn apply(&self, timestamp: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self {
+            ClockCorrection::Offset(offset) => timestamp + *offset,
+            ClockCorrection::Drift(a, b) => {
+                let span = (b.recorded - a.recorded).num_milliseconds() as f64;
+                let offset_a = (a.actual - a.recorded).num_milliseconds() as f64;
+                let offset_b = (b.actual - b.recorded).num_milliseconds() as f64;
+                let position = (timestamp - a.recorded).num_milliseconds() as f64 / span;
+                let offset = offset_a + (offset_b - offset_a) * position;
+                timestamp + Duration::milliseconds(offset.round() as i64)
+            }
+        }
+    }
+
+    /// Human readable description, recorded in the deployment comments.
+    fn describe(&self) -> String {
+        match self {
+            ClockCorrection::Offset(offset) => {
+                format!("timestamps shifted by {} seconds", offset.num_seconds())
+            }
+            ClockCorrection::Drift(a, b) => format!(
+                "timestamps corrected for clock drift ({} recorded as {}, {} recorded as {})",
+                a.actual.to_rfc3339(),
+                a.recorded.to_rfc3339(),
+                b.actual.to_rfc3339(),
+                b.recorded.to_rfc3339()
+            ),
+        }
+    }
+}
+
+impl Package {
+    /// Correct the clock of a deployment: shifts the timestamps of its media, the
+    /// `eventStart`/`eventEnd` of its observations and its deployment window, and records
+    /// the correction in the deployment comments.
+    pub fn correct_clock(
+        &mut self,
+        deployment_id: &str,
+        correction: &ClockCorrection,
+    ) -> Result<(), CorrectionError> {
+        if let ClockCorrection::Drift(a, b) = correction {
+            if a.recorded == b.recorded {
+                return Err(CorrectionError::InvalidReferencePoints);
+            }
+        }
+        let deployment = self
+            .deployments
+            .iter_mut()
+            .find(|d| d.deployment_id == deployment_id)
+            .ok_or_else(|| CorrectionError::UnknownDeployment(deployment_id.to_string()))?;
+
+        deployment.deployment_start = correction.apply(deployment.deployment_start);
+        deployment.deployment_end = correction.apply(deployment.deployment_end);
+        let note = format!("Clock correction: {}.", correction.describe());
+        deployment.comments = Some(match deployment.comments.take() {
+            Some(comments) if !comments.is_empty() => format!("{} {}", comments, note),
+            _ => note,
+        });
+
+        for medium in self
+            .media
+            .iter_mut()
+            .filter(|m| m.deployment_id == deployment_id)
+        {
+            medium.timestamp = correction.apply(medium.timestamp);
+        }
+
+        for observation in self
+            .observations
+            .iter_mut()
+            .filter(|o| o.deployment_id == deployment_id)
+        {
+            observation.event_start = observation.event_start.map(|t| correction.apply(t));
+            observation.event_end = observation.event_end.map(|t| correction.apply(t));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn timestamp(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(s).unwrap()
+    }
+
+    #[test]
+    fn linear_drift() {
+        let correction = ClockCorrection::Drift(
+            ReferencePoint {
+                recorded: timestamp("2020-01-01T00:00:00Z"),
+                actual: timestamp("2020-01-01T00:00:00Z"),
+            },
+            ReferencePoint {
+                recorded: timestamp("2020-01-11T00:00:00Z"),
+                actual: timestamp("2020-01-11T00:10:00Z"),
+            },
+        );
+        assert_eq!(
+            correction.apply(timestamp("2020-01-06T02:00:00+02:00")),
+            timestamp("2020-01-06T02:05:00+02:00")
+        );
+        assert_eq!(
+            correction.apply(timestamp("2020-01-11T00:00:00Z")),
+            timestamp("2020-01-11T00:10:00Z")
+        );
+    }
+
+    #[test]
+    fn shift_deployment_by_a_year() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let original = package.clone();
+        let offset = Duration::days(366);
+        package
+            .correct_clock("00a2c20d", &ClockCorrection::Offset(offset))
+            .unwrap();
+
+        let deployment = package.deployment("00a2c20d").unwrap();
+        assert_eq!(
+            deployment.deployment_start,
+            original.deployment("00a2c20d").unwrap().deployment_start + offset
+        );
+        assert!(deployment
+            .comments
+            .as_ref()
+            .unwrap()
+            .contains("Clock correction: timestamps shifted by 31622400 seconds."));
+
+        for (medium, before) in package.media.iter().zip(&original.media) {
+            if medium.deployment_id == "00a2c20d" {
+                assert_eq!(medium.timestamp, before.timestamp + offset);
+            } else {
+                assert_eq!(medium.timestamp, before.timestamp);
+            }
+        }
+        for (observation, before) in package.observations.iter().zip(&original.observations) {
+            if observation.deployment_id == "00a2c20d" {
+                assert_eq!(
+                    observation.event_start,
+                    before.event_start.map(|t| t + offset)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_corrections() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        assert_eq!(
+            package.correct_clock("unknown", &ClockCorrection::Offset(Duration::hours(1))),
+            Err(CorrectionError::UnknownDeployment("unknown".to_string()))
+        );
+
+        let point = ReferencePoint {
+            recorded: timestamp("2020-01-01T00:00:00Z"),
+            actual: timestamp("2020-01-01T01:00:00Z"),
+        };
+        assert_eq!(
+            package.correct_clock("00a2c20d", &ClockCorrection::Drift(point, point)),
+            Err(CorrectionError::InvalidReferencePoints)
+        );
+    }
+}
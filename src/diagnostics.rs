@@ -0,0 +1,258 @@
+//! Detection of deployments with suspicious timestamps.
+//!
+//! Flags likely clock problems: media outside the deployment window, media timestamped at
+//! midnight on a camera factory default date, nocturnal activity of diurnal species (which
+//! suggests an am/pm swap) and long gaps followed by bursts of media.
+
+use crate::activity::clock_time;
+use crate::sun::sun_time;
+use crate::{Deployment, Medium, Package};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Timelike};
+use std::f64::consts::PI;
+
+/// Options for [`Package::diagnose_timestamps`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagnosticsOptions {
+    /// Dates cameras reset to when their clock was not set.
+    pub factory_default_dates: Vec<NaiveDate>,
+
+    /// Scientific names of species that are only active during daytime.
+    pub diurnal_species: Vec<String>,
+
+    /// Proportion of nighttime observations of diurnal species above which an am/pm swap is
+    /// suspected.
+    pub nocturnal_threshold: f64,
+
+    /// Minimum number of observations of diurnal species needed to suspect an am/pm swap.
+    pub min_diurnal_observations: usize,
+
+    /// Time without media after which a burst is suspicious.
+    pub gap: Duration,
+
+    /// Number of media that, when recorded within `burst_window` right after a gap, form a
+    /// burst.
+    pub burst_size: usize,
+
+    /// Time window in which `burst_size` media form a burst.
+    pub burst_window: Duration,
+}
+
+impl Default for DiagnosticsOptions {
+    fn default() -> Self {
+        DiagnosticsOptions {
+            factory_default_dates: [(2000, 1, 1), (2010, 1, 1), (2015, 1, 1), (2016, 1, 1)]
+                .iter()
+                .filter_map(|(y, m, d)| NaiveDate::from_ymd_opt(*y, *m, *d))
+                .collect(),
+            diurnal_species: Vec::new(),
+            nocturnal_threshold: 0.5,
+            min_diurnal_observations: 3,
+            gap: Duration::days(7),
+            burst_size: 50,
+            burst_window: Duration::minutes(10),
+        }
+    }
+}
+
+/// Timestamp diagnostics of a deployment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimestampDiagnostics {
+    /// Identifier of the deployment.
+    pub deployment_id: String,
+
+    /// Media files recorded before `deploymentStart` or after `deploymentEnd`.
+    pub media_outside_window: Vec<String>,
+
+    /// Media files timestamped at exactly midnight on a factory default date.
+    pub factory_default_media: Vec<String>,
+
+    /// Proportion of observations of diurnal species recorded at night, if there are enough
+    /// of them.
+    pub nocturnal_proportion: Option<f64>,
+
+    /// True if the nocturnal proportion suggests an am/pm swap.
+    pub suspected_am_pm_swap: bool,
+
+    /// Timestamps of the first media file of each burst following a long gap.
+    pub bursts_after_gap: Vec<DateTime<FixedOffset>>,
+}
+
+impl TimestampDiagnostics {
+    /// True if any problem was detected.
+    pub fn has_issues(&self) -> bool {
+        !self.media_outside_window.is_empty()
+            || !self.factory_default_media.is_empty()
+            || self.suspected_am_pm_swap
+            || !self.bursts_after_gap.is_empty()
+    }
+}
+
+/// True if the timestamp is at night: relative to the sun if the deployment has coordinates,
+/// otherwise between 18:00 and 06:00 in the timezone of the deployment start.
+fn is_night(deployment: &Deployment, timestamp: &DateTime<FixedOffset>) -> bool {
+    let time = match (deployment.latitude, deployment.longitude) {
+        (Some(latitude), Some(longitude)) => sun_time(timestamp, latitude, longitude),
+        _ => None,
+    }
+    .unwrap_or_else(|| {
+        clock_time(&timestamp.with_timezone(&deployment.deployment_start.timezone()))
+    });
+
+    !(PI / 2.0..3.0 * PI / 2.0).contains(&time)
+}
+
+fn bursts_after_gap(media: &[&Medium], options: &DiagnosticsOptions) -> Vec<DateTime<FixedOffset>> {
+    let mut timestamps: Vec<DateTime<FixedOffset>> = media.iter().map(|m| m.timestamp).collect();
+    timestamps.sort();
+
+    let mut bursts = Vec::new();
+    for i in 1..timestamps.len() {
+        if timestamps[i] - timestamps[i - 1] <= options.gap {
+            continue;
+        }
+        let in_window = timestamps[i..]
+            .iter()
+            .take_while(|t| **t - timestamps[i] <= options.burst_window)
+            .count();
+        if in_window >= options.burst_size {
+            bursts.push(timestamps[i]);
+        }
+    }
+    bursts
+}
+
+impl Package {
+    /// Timestamp diagnostics per deployment.
+    pub fn diagnose_timestamps(&self, options: &DiagnosticsOptions) -> Vec<TimestampDiagnostics> {
+        self.deployments
+            .iter()
+            .map(|deployment| {
+                let media: Vec<&Medium> = self
+                    .media
+                    .iter()
+                    .filter(|m| m.deployment_id == deployment.deployment_id)
+                    .collect();
+
+                let media_outside_window = media
+                    .iter()
+                    .filter(|m| {
+                        m.timestamp < deployment.deployment_start
+                            || m.timestamp > deployment.deployment_end
+                    })
+                    .map(|m| m.media_id.clone())
+                    .collect();
+
+                let factory_default_media = media
+                    .iter()
+                    .filter(|m| {
+                        m.timestamp.num_seconds_from_midnight() == 0
+                            && options
+                                .factory_default_dates
+                                .contains(&m.timestamp.date_naive())
+                    })
+                    .map(|m| m.media_id.clone())
+                    .collect();
+
+                let diurnal: Vec<bool> = self
+                    .observations
+                    .iter()
+                    .filter(|o| o.deployment_id == deployment.deployment_id)
+                    .filter(|o| {
+                        o.scientific_name
+                            .as_ref()
+                            .map(|name| options.diurnal_species.contains(name))
+                            .unwrap_or(false)
+                    })
+                    .filter_map(|o| o.event_start)
+                    .map(|timestamp| is_night(deployment, &timestamp))
+                    .collect();
+                let nocturnal_proportion = (!diurnal.is_empty()
+                    && diurnal.len() >= options.min_diurnal_observations)
+                    .then(|| {
+                        diurnal.iter().filter(|night| **night).count() as f64 / diurnal.len() as f64
+                    });
+
+                TimestampDiagnostics {
+                    deployment_id: deployment.deployment_id.clone(),
+                    media_outside_window,
+                    factory_default_media,
+                    nocturnal_proportion,
+                    suspected_am_pm_swap: nocturnal_proportion
+                        .map(|p| p > options.nocturnal_threshold)
+                        .unwrap_or(false),
+                    bursts_after_gap: bursts_after_gap(&media, options),
+                }
+            })
+            .collect()
+    }
+
+    /// Diagnose timestamps and set `timestampIssues = true` on deployments with issues.
+    /// Deployments without issues are left untouched.
+    pub fn flag_timestamp_issues(
+        &mut self,
+        options: &DiagnosticsOptions,
+    ) -> Vec<TimestampDiagnostics> {
+        let diagnostics = self.diagnose_timestamps(options);
+        for (deployment, diagnostics) in self.deployments.iter_mut().zip(&diagnostics) {
+            if diagnostics.has_issues() {
+                deployment.timestamp_issues = Some(true);
+            }
+        }
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixture_has_no_issues() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let diagnostics = package.flag_timestamp_issues(&DiagnosticsOptions::default());
+        assert_eq!(diagnostics.len(), 4);
+        assert!(diagnostics.iter().all(|d| !d.has_issues()));
+        assert!(package
+            .deployments
+            .iter()
+            .all(|d| d.timestamp_issues != Some(true)));
+    }
+
+    #[test]
+    fn detect_clock_problems() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let factory_default = DateTime::parse_from_rfc3339("2000-01-01T00:00:00+02:00").unwrap();
+        package.media[0].timestamp = factory_default;
+
+        let options = DiagnosticsOptions {
+            diurnal_species: vec!["Anas platyrhynchos".to_string()],
+            min_diurnal_observations: 1,
+            burst_size: 2,
+            gap: Duration::days(1),
+            ..Default::default()
+        };
+        let diagnostics = package.flag_timestamp_issues(&options);
+        let first = &diagnostics[0];
+        assert_eq!(
+            first.media_outside_window,
+            vec![package.media[0].media_id.clone()]
+        );
+        assert_eq!(
+            first.factory_default_media,
+            vec![package.media[0].media_id.clone()]
+        );
+        assert!(!first.bursts_after_gap.is_empty());
+        assert!(first.nocturnal_proportion.is_some());
+        assert_eq!(package.deployments[0].timestamp_issues, Some(true));
+    }
+
+    #[test]
+    fn night_relative_to_sun() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let deployment = &package.deployments[0];
+        let noon = DateTime::parse_from_rfc3339("2020-06-01T12:00:00+02:00").unwrap();
+        let midnight = DateTime::parse_from_rfc3339("2020-06-01T00:30:00+02:00").unwrap();
+        assert!(!is_night(deployment, &noon));
+        assert!(is_night(deployment, &midnight));
+    }
+}
@@ -0,0 +1,302 @@
+//! Camera trap distance sampling (Howe et al. 2017).
+//!
+//! Distances to animals are taken at snapshot moments, here the media files captured with
+//! `captureMethod = timeLapse`. The data is exported as the flat file expected by the R
+//! `Distance` package: one row per detected object, plus one row without an object for each
+//! sample (deployment) without detections, so its effort is still accounted for.
+
+use crate::effort::EffortOptions;
+use crate::{CaptureMethod, Deployment, ObservationType, Package, WriteDataPackageCsv};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Options for [`Package::distance_data`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceOptions {
+    /// Scientific name of the species. All animal observations are used if `None`.
+    pub scientific_name: Option<String>,
+
+    /// Distance bin cutpoints in meters (e.g. `[0, 2, 4, 6, 8]`). Distances are exported as
+    /// exact values if empty; otherwise distances outside the outer cutpoints are truncated.
+    /// Bins include their start but not their end, except the last bin, which includes the
+    /// outer cutpoint.
+    pub cutpoints: Vec<f64>,
+
+    /// Time between snapshot moments, in seconds. Only used to estimate the number of snapshot
+    /// moments of deployments without time-lapse media files.
+    pub snapshot_interval: f64,
+
+    /// Label of the study region.
+    pub region_label: String,
+
+    /// Area of the study region. Zero to only estimate detection probability and encounter
+    /// rate.
+    pub area: f64,
+
+    /// Options used to compute the active time of each deployment.
+    pub effort: EffortOptions,
+}
+
+impl Default for DistanceOptions {
+    fn default() -> Self {
+        DistanceOptions {
+            scientific_name: None,
+            cutpoints: Vec::new(),
+            snapshot_interval: 2.0,
+            region_label: "region".to_string(),
+            area: 0.0,
+            effort: EffortOptions::default(),
+        }
+    }
+}
+
+/// Row of the flat file expected by the R `Distance` package.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DistanceRecord {
+    /// Label of the study region.
+    #[serde(rename = "Region.Label")]
+    pub region_label: String,
+
+    /// Area of the study region.
+    #[serde(rename = "Area")]
+    pub area: f64,
+
+    /// Sample (deploymentID).
+    #[serde(rename = "Sample.Label")]
+    pub sample_label: String,
+
+    /// Temporal effort: the number of time-lapse media files of the deployment, or, if it has
+    /// none, the active time divided by the snapshot interval.
+    #[serde(rename = "Effort")]
+    pub effort: f64,
+
+    /// Active time of the camera, in camera-seconds.
+    pub camera_seconds: f64,
+
+    /// Identifier of the detected object, `None` for samples without detections.
+    pub object: Option<usize>,
+
+    /// Exact distance, in meters, if no cutpoints were given.
+    pub distance: Option<f64>,
+
+    /// Start of the distance bin, in meters.
+    pub distbegin: Option<f64>,
+
+    /// End of the distance bin, in meters.
+    pub distend: Option<f64>,
+
+    /// Number of individuals (cluster size).
+    pub size: Option<u32>,
+
+    /// Identifier of the observation the distance was taken from.
+    #[serde(rename = "observationID")]
+    pub observation_id: Option<String>,
+}
+
+impl WriteDataPackageCsv<DistanceRecord> for Vec<DistanceRecord> {}
+
+impl Package {
+    /// Active time of a deployment in camera-seconds. For each activity-triggered media file
+    /// the camera ignored activity during `cameraDelay` seconds, which is subtracted.
+    pub fn camera_seconds(&self, deployment: &Deployment, options: &EffortOptions) -> f64 {
+        let active: f64 = self
+            .active_intervals(deployment, options)
+            .iter()
+            .map(|(start, end)| (*end - *start).num_milliseconds() as f64 / 1000.0)
+            .sum();
+        let triggers = self
+            .media
+            .iter()
+            .filter(|m| m.deployment_id == deployment.deployment_id)
+            .filter(|m| m.capture_method == Some(CaptureMethod::ActivityDetection))
+            .count();
+        let delay = deployment.camera_delay.unwrap_or(0.0) * triggers as f64;
+
+        (active - delay).max(0.0)
+    }
+
+    /// Distance sampling data of observations with an `individualPositionRadius` on
+    /// time-lapse media files, ordered by deployment.
+    pub fn distance_data(&self, options: &DistanceOptions) -> Vec<DistanceRecord> {
+        let time_lapse: HashMap<&str, &str> = self
+            .media
+            .iter()
+            .filter(|m| m.capture_method == Some(CaptureMethod::TimeLapse))
+            .map(|m| (m.media_id.as_str(), m.deployment_id.as_str()))
+            .collect();
+        let mut snapshots: HashMap<&str, usize> = HashMap::new();
+        for deployment_id in time_lapse.values() {
+            *snapshots.entry(deployment_id).or_insert(0) += 1;
+        }
+
+        let mut records = Vec::new();
+        let mut object = 0;
+        for deployment in &self.deployments {
+            let camera_seconds = self.camera_seconds(deployment, &options.effort);
+            let effort = match snapshots.get(deployment.deployment_id.as_str()) {
+                Some(snapshots) => *snapshots as f64,
+                None => camera_seconds / options.snapshot_interval,
+            };
+            let record =
+                |object, distance, bin: Option<(f64, f64)>, size, observation_id| DistanceRecord {
+                    region_label: options.region_label.clone(),
+                    area: options.area,
+                    sample_label: deployment.deployment_id.clone(),
+                    effort,
+                    camera_seconds,
+                    object,
+                    distance,
+                    distbegin: bin.map(|(begin, _)| begin),
+                    distend: bin.map(|(_, end)| end),
+                    size,
+                    observation_id,
+                };
+
+            let before = records.len();
+            for observation in self.observations.iter().filter(|o| {
+                o.deployment_id == deployment.deployment_id
+                    && o.observation_type == ObservationType::Animal
+                    && o.media_id
+                        .as_deref()
+                        .map(|media_id| time_lapse.contains_key(media_id))
+                        .unwrap_or(false)
+                    && (options.scientific_name.is_none()
+                        || o.scientific_name == options.scientific_name)
+            }) {
+                let radius = match observation.individual_position_radius {
+                    Some(radius) => radius,
+                    None => continue,
+                };
+                let (distance, bin) = if options.cutpoints.len() < 2 {
+                    (Some(radius), None)
+                } else {
+                    let last = options.cutpoints.len() - 2;
+                    match options
+                        .cutpoints
+                        .windows(2)
+                        .enumerate()
+                        .find(|(i, bin)| {
+                            radius >= bin[0] && (radius < bin[1] || *i == last && radius == bin[1])
+                        })
+                        .map(|(_, bin)| bin)
+                    {
+                        Some(bin) => (None, Some((bin[0], bin[1]))),
+                        None => continue,
+                    }
+                };
+
+                object += 1;
+                records.push(record(
+                    Some(object),
+                    distance,
+                    bin,
+                    observation.count,
+                    Some(observation.observation_id.clone()),
+                ));
+            }
+
+            if records.len() == before {
+                records.push(record(None, None, None, None, None));
+            }
+        }
+
+        records
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::test::temp_path;
+
+    fn package_with_distances() -> Package {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let time_lapse: Vec<String> = package
+            .media
+            .iter()
+            .filter(|m| m.capture_method == Some(CaptureMethod::TimeLapse))
+            .map(|m| m.media_id.clone())
+            .collect();
+        let template = package.observations[0].clone();
+        for (i, media_id) in time_lapse.iter().enumerate() {
+            let medium = package.medium(media_id).unwrap().clone();
+            package.observations.push(crate::Observation {
+                observation_id: format!("{}_distance", media_id),
+                deployment_id: medium.deployment_id.clone(),
+                media_id: Some(media_id.clone()),
+                individual_position_radius: Some(1.5 + i as f64 * 2.0),
+                ..template.clone()
+            });
+        }
+        package
+    }
+
+    #[test]
+    fn camera_seconds_accounts_for_delay() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let deployment = package.deployments[0].clone();
+        let without_delay = package.camera_seconds(&deployment, &EffortOptions::default());
+        let expected = (deployment.deployment_end - deployment.deployment_start).num_seconds();
+        assert_eq!(without_delay, expected as f64);
+
+        package.deployments[0].camera_delay = Some(10.0);
+        let deployment = package.deployments[0].clone();
+        let with_delay = package.camera_seconds(&deployment, &EffortOptions::default());
+        assert_eq!(without_delay - with_delay, 1800.0);
+    }
+
+    #[test]
+    fn bins_are_half_open() {
+        let package = package_with_distances();
+        let records = package.distance_data(&DistanceOptions {
+            cutpoints: vec![0.0, 1.5, 3.5],
+            ..Default::default()
+        });
+
+        // 1.5 falls in [1.5, 3.5) only, 3.5 in the last bin and 5.5 is truncated.
+        let bins: Vec<_> = records
+            .iter()
+            .filter(|r| r.object.is_some())
+            .map(|r| (r.distbegin, r.distend))
+            .collect();
+        assert_eq!(bins, vec![(Some(1.5), Some(3.5)), (Some(1.5), Some(3.5))]);
+    }
+
+    #[test]
+    fn binned_distance_data() {
+        let package = package_with_distances();
+        let records = package.distance_data(&DistanceOptions {
+            cutpoints: vec![0.0, 2.0, 4.0],
+            ..Default::default()
+        });
+
+        let objects: Vec<_> = records.iter().filter(|r| r.object.is_some()).collect();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(
+            (objects[0].distbegin, objects[0].distend),
+            (Some(0.0), Some(2.0))
+        );
+        assert_eq!(
+            (objects[1].distbegin, objects[1].distend),
+            (Some(2.0), Some(4.0))
+        );
+
+        let samples: std::collections::HashSet<_> =
+            records.iter().map(|r| r.sample_label.as_str()).collect();
+        assert_eq!(samples.len(), 4);
+        assert!(records
+            .iter()
+            .filter(|r| r.sample_label == "577b543a")
+            .all(|r| r.effort == 3.0));
+        assert!(records
+            .iter()
+            .filter(|r| r.sample_label != "577b543a")
+            .all(|r| r.effort == r.camera_seconds / 2.0));
+
+        let path = temp_path("distance.csv");
+        records.to_file(&path).unwrap();
+        let header = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(header.starts_with("Region.Label,Area,Sample.Label,Effort"));
+    }
+}
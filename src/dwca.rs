@@ -0,0 +1,492 @@
+//! Darwin Core Archive export for publication on GBIF.
+//!
+//! Follows the Camtrap DP to Darwin Core mapping used by GBIF: deployments and events form an
+//! Event core (events have their deployment as `parentEventID`), event-based animal
+//! observations form the Occurrence extension (`basisOfRecord = MachineObservation`) and the
+//! media of each event form the Simple Multimedia extension. Media-based observations are not
+//! exported, as they duplicate the event-based ones.
+
+use crate::eml::EmlVersion;
+use crate::package::resource_path;
+use crate::util::{escape_xml, name};
+use crate::{
+    Deployment, Medium, Observation, ObservationLevel, ObservationType, Package, PackageError,
+};
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+use std::path::Path;
+
+const DWC: &str = "http://rs.tdwg.org/dwc/terms/";
+const DCTERMS: &str = "http://purl.org/dc/terms/";
+
+const EVENT_TERMS: &[&str] = &[
+    "eventID",
+    "parentEventID",
+    "eventDate",
+    "samplingProtocol",
+    "samplingEffort",
+    "habitat",
+    "eventRemarks",
+    "locationID",
+    "locality",
+    "decimalLatitude",
+    "decimalLongitude",
+    "geodeticDatum",
+    "coordinateUncertaintyInMeters",
+    "coordinatePrecision",
+    "type",
+    "license",
+    "rightsHolder",
+    "datasetID",
+    "datasetName",
+];
+
+const OCCURRENCE_TERMS: &[&str] = &[
+    "eventID",
+    "occurrenceID",
+    "basisOfRecord",
+    "individualCount",
+    "sex",
+    "lifeStage",
+    "behavior",
+    "occurrenceStatus",
+    "occurrenceRemarks",
+    "organismID",
+    "identifiedBy",
+    "dateIdentified",
+    "identificationRemarks",
+    "taxonID",
+    "scientificName",
+    "taxonRank",
+    "vernacularName",
+    "kingdom",
+];
+
+const MULTIMEDIA_TERMS: &[&str] = &[
+    "eventID",
+    "identifier",
+    "type",
+    "format",
+    "title",
+    "created",
+    "license",
+    "rightsHolder",
+];
+
+/// Data file of the archive.
+struct DataFile {
+    name: &'static str,
+    row_type: &'static str,
+    terms: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+impl Package {
+    /// Write the package as a Darwin Core Archive to a directory: `event.txt`,
+    /// `occurrence.txt`, `multimedia.txt`, `meta.xml` and `eml.xml`. The directory is created
+    /// if it does not exist. Dataset level terms (license, rights holder, taxon identifiers)
+    /// and the EML document are taken from the package metadata, if available.
+    pub fn to_dwca(&self, path: &str) -> Result<(), PackageError> {
+        let dir = Path::new(path);
+        std::fs::create_dir_all(dir).map_err(PackageError::Io)?;
+
+        let files = [
+            DataFile {
+                name: "event.txt",
+                row_type: "http://rs.tdwg.org/dwc/terms/Event",
+                terms: EVENT_TERMS,
+                rows: self.dwc_events(),
+            },
+            DataFile {
+                name: "occurrence.txt",
+                row_type: "http://rs.tdwg.org/dwc/terms/Occurrence",
+                terms: OCCURRENCE_TERMS,
+                rows: self.dwc_occurrences(),
+            },
+            DataFile {
+                name: "multimedia.txt",
+                row_type: "http://rs.gbif.org/terms/1.0/Multimedia",
+                terms: MULTIMEDIA_TERMS,
+                rows: self.dwc_multimedia(),
+            },
+        ];
+
+        for file in &files {
+            let mut wtr = csv::WriterBuilder::new()
+                .delimiter(b'\t')
+                .quote_style(csv::QuoteStyle::Never)
+                .from_path(resource_path(dir, file.name))
+                .map_err(PackageError::Csv)?;
+            wtr.write_record(file.terms).map_err(PackageError::Csv)?;
+            for row in &file.rows {
+                wtr.write_record(row.iter().map(|value| sanitize(value)))
+                    .map_err(PackageError::Csv)?;
+            }
+            wtr.flush().map_err(PackageError::Io)?;
+        }
+
+        std::fs::write(resource_path(dir, "meta.xml"), meta_xml(&files))
+            .map_err(PackageError::Io)?;
+        let eml = self
+            .metadata
+            .clone()
+            .unwrap_or_default()
+            .to_eml(EmlVersion::default());
+        std::fs::write(resource_path(dir, "eml.xml"), eml).map_err(PackageError::Io)?;
+        Ok(())
+    }
+
+    /// Event-based animal observations, the records published as occurrences. Observations of
+    /// unknown deployments are left out, as their event would have no row in the event core.
+    fn dwc_observations(&self) -> impl Iterator<Item = &Observation> {
+        self.observations.iter().filter(|o| {
+            o.observation_level == ObservationLevel::Event
+                && o.observation_type == ObservationType::Animal
+                && o.event_id.is_some()
+                && self.deployment(&o.deployment_id).is_some()
+        })
+    }
+
+    /// Event core rows: one per deployment, followed by one per event with occurrences.
+    fn dwc_events(&self) -> Vec<Vec<String>> {
+        let metadata = self.metadata.clone().unwrap_or_default();
+        let dataset = |row: &mut Vec<String>| {
+            row.push("StillImage".to_string());
+            row.push(
+                metadata
+                    .license("data")
+                    .and_then(|l| l.name.clone().or_else(|| l.path.clone()))
+                    .unwrap_or_default(),
+            );
+            row.push(
+                metadata
+                    .contributors_with_role("rightsHolder")
+                    .next()
+                    .map(|c| c.title.clone())
+                    .unwrap_or_default(),
+            );
+            row.push(metadata.id.clone().unwrap_or_default());
+            row.push(metadata.title.clone().unwrap_or_default());
+        };
+        let location = |row: &mut Vec<String>, deployment: &Deployment| {
+            row.push(deployment.location_id.clone().unwrap_or_default());
+            row.push(deployment.location_name.clone().unwrap_or_default());
+            row.push(optional(deployment.latitude));
+            row.push(optional(deployment.longitude));
+            row.push("EPSG:4326".to_string());
+            row.push(optional(deployment.location_radius));
+            row.push(optional(metadata.coordinate_precision));
+        };
+
+        let mut rows = Vec::new();
+        for deployment in &self.deployments {
+            let interval = date_interval(&deployment.deployment_start, &deployment.deployment_end);
+            let mut row = vec![
+                deployment.deployment_id.clone(),
+                String::new(),
+                interval.clone(),
+                sampling_protocol(deployment),
+                interval,
+                deployment.habitat.clone().unwrap_or_default(),
+                deployment.comments.clone().unwrap_or_default(),
+            ];
+            location(&mut row, deployment);
+            dataset(&mut row);
+            rows.push(row);
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for observation in self.dwc_observations() {
+            let event_id = observation.event_id.clone().unwrap_or_default();
+            if !seen.insert(event_id.clone()) {
+                continue;
+            }
+            let deployment = match self.deployment(&observation.deployment_id) {
+                Some(deployment) => deployment,
+                None => continue,
+            };
+            let mut row = vec![
+                event_id,
+                deployment.deployment_id.clone(),
+                event_date(observation),
+                sampling_protocol(deployment),
+                date_interval(&deployment.deployment_start, &deployment.deployment_end),
+                deployment.habitat.clone().unwrap_or_default(),
+                String::new(),
+            ];
+            location(&mut row, deployment);
+            dataset(&mut row);
+            rows.push(row);
+        }
+        rows
+    }
+
+    /// Occurrence extension rows: one per event-based animal observation.
+    fn dwc_occurrences(&self) -> Vec<Vec<String>> {
+        let metadata = self.metadata.clone().unwrap_or_default();
+        self.dwc_observations()
+            .map(|observation| {
+                let taxon = observation
+                    .scientific_name
+                    .as_deref()
+                    .and_then(|scientific_name| metadata.taxon(scientific_name));
+                vec![
+                    observation.event_id.clone().unwrap_or_default(),
+                    observation.observation_id.clone(),
+                    "MachineObservation".to_string(),
+                    optional(observation.count),
+                    observation.sex.as_ref().map(name).unwrap_or_default(),
+                    observation
+                        .life_stage
+                        .as_ref()
+                        .map(name)
+                        .unwrap_or_default(),
+                    observation.behavior.clone().unwrap_or_default(),
+                    "present".to_string(),
+                    observation.observation_comments.clone().unwrap_or_default(),
+                    observation.individual_id.clone().unwrap_or_default(),
+                    observation.classified_by.clone().unwrap_or_default(),
+                    observation
+                        .classification_timestamp
+                        .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+                        .unwrap_or_default(),
+                    identification_remarks(observation),
+                    taxon.and_then(|t| t.taxon_id.clone()).unwrap_or_default(),
+                    observation.scientific_name.clone().unwrap_or_default(),
+                    taxon.and_then(|t| t.taxon_rank.clone()).unwrap_or_default(),
+                    taxon
+                        .and_then(|t| t.vernacular_names.get("eng").cloned())
+                        .unwrap_or_default(),
+                    "Animalia".to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    /// Simple Multimedia extension rows: the media recorded during each exported event.
+    fn dwc_multimedia(&self) -> Vec<Vec<String>> {
+        let metadata = self.metadata.clone().unwrap_or_default();
+        let license = metadata
+            .license("media")
+            .and_then(|l| l.path.clone().or_else(|| l.name.clone()))
+            .unwrap_or_default();
+        let rights_holder = metadata
+            .contributors_with_role("rightsHolder")
+            .next()
+            .map(|c| c.title.clone())
+            .unwrap_or_default();
+
+        let mut seen = std::collections::HashSet::new();
+        let mut rows = Vec::new();
+        for observation in self.dwc_observations() {
+            let event_id = observation.event_id.clone().unwrap_or_default();
+            if !seen.insert(event_id.clone()) {
+                continue;
+            }
+            let (start, end) = match (observation.event_start, observation.event_end) {
+                (Some(start), Some(end)) => (start, end),
+                _ => continue,
+            };
+            let mut media: Vec<&Medium> = self
+                .media
+                .iter()
+                .filter(|m| {
+                    m.deployment_id == observation.deployment_id
+                        && m.timestamp >= start
+                        && m.timestamp <= end
+                })
+                .collect();
+            media.sort_by_key(|m| m.timestamp);
+            for medium in media {
+                rows.push(vec![
+                    event_id.clone(),
+                    medium.file_path.clone(),
+                    media_type(&medium.file_mediatype).to_string(),
+                    medium.file_mediatype.clone(),
+                    medium
+                        .file_name
+                        .clone()
+                        .unwrap_or_else(|| medium.media_id.clone()),
+                    medium.timestamp.to_rfc3339_opts(SecondsFormat::Secs, true),
+                    license.clone(),
+                    rights_holder.clone(),
+                ]);
+            }
+        }
+        rows
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Tabs and line breaks are not allowed in unquoted tab-separated values.
+fn sanitize(value: &str) -> String {
+    value.replace(['\t', '\r', '\n'], " ")
+}
+
+fn date_interval(start: &DateTime<FixedOffset>, end: &DateTime<FixedOffset>) -> String {
+    format!(
+        "{}/{}",
+        start.to_rfc3339_opts(SecondsFormat::Secs, true),
+        end.to_rfc3339_opts(SecondsFormat::Secs, true)
+    )
+}
+
+fn event_date(observation: &Observation) -> String {
+    match (observation.event_start, observation.event_end) {
+        (Some(start), Some(end)) if start == end => {
+            start.to_rfc3339_opts(SecondsFormat::Secs, true)
+        }
+        (Some(start), Some(end)) => date_interval(&start, &end),
+        (Some(start), None) => start.to_rfc3339_opts(SecondsFormat::Secs, true),
+        _ => String::new(),
+    }
+}
+
+fn sampling_protocol(deployment: &Deployment) -> String {
+    match deployment.bait_use {
+        Some(true) => "camera trap with bait".to_string(),
+        Some(false) => "camera trap without bait".to_string(),
+        None => "camera trap".to_string(),
+    }
+}
+
+fn identification_remarks(observation: &Observation) -> String {
+    let mut remarks = Vec::new();
+    if let Some(method) = &observation.classification_method {
+        remarks.push(format!("classified by a {}", name(method)));
+    }
+    if let Some(probability) = observation.classification_probability {
+        remarks.push(format!("with {}% certainty", (probability * 100.0).round()));
+    }
+    remarks.join(" ")
+}
+
+/// Dublin Core type of a media file.
+fn media_type(mediatype: &str) -> &'static str {
+    if mediatype.starts_with("video/") {
+        "MovingImage"
+    } else {
+        "StillImage"
+    }
+}
+
+fn term_uri(term: &str) -> String {
+    match term {
+        "type" | "license" | "rightsHolder" | "identifier" | "format" | "title" | "created" => {
+            format!("{}{}", DCTERMS, term)
+        }
+        _ => format!("{}{}", DWC, term),
+    }
+}
+
+fn meta_xml(files: &[DataFile]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<archive xmlns=\"http://rs.tdwg.org/dwc/text/\" metadata=\"eml.xml\">\n");
+    for (i, file) in files.iter().enumerate() {
+        let (element, id) = if i == 0 {
+            ("core", "id")
+        } else {
+            ("extension", "coreid")
+        };
+        xml.push_str(&format!(
+            "  <{} encoding=\"UTF-8\" fieldsTerminatedBy=\"\\t\" linesTerminatedBy=\"\\n\" \
+             fieldsEnclosedBy=\"\" ignoreHeaderLines=\"1\" rowType=\"{}\">\n",
+            element,
+            escape_xml(file.row_type)
+        ));
+        xml.push_str(&format!(
+            "    <files>\n      <location>{}</location>\n    </files>\n",
+            file.name
+        ));
+        xml.push_str(&format!("    <{} index=\"0\"/>\n", id));
+        for (index, term) in file.terms.iter().enumerate() {
+            xml.push_str(&format!(
+                "    <field index=\"{}\" term=\"{}\"/>\n",
+                index,
+                term_uri(term)
+            ));
+        }
+        xml.push_str(&format!("  </{}>\n", element));
+    }
+    xml.push_str("</archive>\n");
+    xml
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::test::temp_path;
+
+    #[test]
+    fn dwca_files() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        package.metadata = Some(crate::metadata::test::example());
+        let dir = temp_path("dwca");
+        package.to_dwca(&dir).unwrap();
+        let read =
+            |name: &str| std::fs::read_to_string(std::path::Path::new(&dir).join(name)).unwrap();
+
+        let occurrences = read("occurrence.txt");
+        let mut lines = occurrences.lines();
+        assert_eq!(
+            lines.next().unwrap().split('\t').count(),
+            OCCURRENCE_TERMS.len()
+        );
+        assert_eq!(lines.clone().count(), 29);
+        assert!(lines.all(|line| line.contains("\tMachineObservation\t")));
+
+        let mallard = occurrences
+            .lines()
+            .find(|line| line.contains("Anas platyrhynchos"))
+            .unwrap();
+        assert!(mallard.contains("https://www.checklistbank.org/dataset/COL2023/taxon/DGP6"));
+
+        let events = read("event.txt");
+        assert!(events.lines().any(|line| line.starts_with("00a2c20d\t\t")));
+        assert!(events
+            .lines()
+            .all(|line| line.split('\t').count() == EVENT_TERMS.len()));
+
+        let multimedia = read("multimedia.txt");
+        assert!(multimedia.lines().count() > 29);
+
+        let meta = read("meta.xml");
+        assert!(meta.contains("rowType=\"http://rs.tdwg.org/dwc/terms/Event\""));
+        assert!(meta.contains("term=\"http://rs.tdwg.org/dwc/terms/basisOfRecord\""));
+        assert!(read("eml.xml").contains("<eml:eml"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dwca_unknown_deployment() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        package
+            .deployments
+            .retain(|d| d.deployment_id != "00a2c20d");
+        let dir = temp_path("dwca_unknown_deployment");
+        package.to_dwca(&dir).unwrap();
+        let read =
+            |name: &str| std::fs::read_to_string(std::path::Path::new(&dir).join(name)).unwrap();
+        let (events, occurrences, multimedia) = (
+            read("event.txt"),
+            read("occurrence.txt"),
+            read("multimedia.txt"),
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let event_ids: std::collections::HashSet<&str> = events
+            .lines()
+            .skip(1)
+            .map(|line| line.split('\t').next().unwrap())
+            .collect();
+        for extension in [&occurrences, &multimedia] {
+            let mut lines = extension.lines().skip(1).peekable();
+            assert!(lines.peek().is_some());
+            assert!(lines.all(|line| event_ids.contains(line.split('\t').next().unwrap())));
+        }
+        assert!(occurrences.lines().count() < 30);
+    }
+}
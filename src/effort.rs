@@ -0,0 +1,346 @@
+//! Sampling effort (active camera-days) per deployment.
+//!
+//! The effort of a deployment is the time between `deploymentStart` and `deploymentEnd`,
+//! minus any known malfunction intervals. When a camera stopped recording early, the
+//! effective end can be inferred from the timestamp of the last media file.
+
+use crate::{Deployment, Package, WriteDataPackageCsv};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Months, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// Interval during which the camera of a deployment was known not to be recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Malfunction {
+    /// Identifier of the deployment the malfunction applies to.
+    pub deployment_id: String,
+
+    /// Date and time at which the malfunction started.
+    pub start: DateTime<FixedOffset>,
+
+    /// Date and time at which the malfunction ended.
+    pub end: DateTime<FixedOffset>,
+}
+
+/// Period over which effort is summarised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// Calendar day.
+    Day,
+
+    /// ISO 8601 week, starting on Monday.
+    Week,
+
+    /// Calendar month.
+    Month,
+
+    /// Meteorological season (e.g. spring is March to May in the northern hemisphere).
+    /// Seasons are swapped for deployments with a negative latitude.
+    Season,
+}
+
+/// Options for the effort computations.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EffortOptions {
+    /// Intervals during which cameras were not recording.
+    pub malfunctions: Vec<Malfunction>,
+
+    /// If true, the effective end of a deployment is the timestamp of its last media file
+    /// when that is earlier than `deploymentEnd` (e.g. because the camera died early).
+    pub infer_end_from_media: bool,
+}
+
+/// Active sampling effort of a deployment, optionally within a period.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Effort {
+    /// Identifier of the deployment.
+    #[serde(rename = "deploymentID")]
+    pub deployment_id: String,
+
+    /// Label of the period (e.g. `2020-06-01`, `2020-W23`, `2020-06` or `2020-summer`), if
+    /// the effort is summarised per period.
+    pub period: Option<String>,
+
+    /// Start of the active time covered by this record.
+    pub start: DateTime<FixedOffset>,
+
+    /// End of the active time covered by this record.
+    pub end: DateTime<FixedOffset>,
+
+    /// Active camera-days.
+    #[serde(rename = "cameraDays")]
+    pub camera_days: f64,
+}
+
+impl WriteDataPackageCsv<Effort> for Vec<Effort> {}
+
+impl Package {
+    /// Effective end of a deployment: `deploymentEnd`, or the timestamp of the last media file
+    /// if that is earlier and `infer_end_from_media` is set. Never before `deploymentStart`.
+    pub fn effective_end(
+        &self,
+        deployment: &Deployment,
+        options: &EffortOptions,
+    ) -> DateTime<FixedOffset> {
+        if !options.infer_end_from_media {
+            return deployment.deployment_end;
+        }
+
+        self.media
+            .iter()
+            .filter(|m| m.deployment_id == deployment.deployment_id)
+            .map(|m| m.timestamp)
+            .max()
+            .filter(|last| *last < deployment.deployment_end)
+            .map(|last| {
+                last.max(deployment.deployment_start)
+                    .with_timezone(&deployment.deployment_end.timezone())
+            })
+            .unwrap_or(deployment.deployment_end)
+    }
+
+    /// Intervals during which the camera of a deployment was active, in chronological order.
+    pub fn active_intervals(
+        &self,
+        deployment: &Deployment,
+        options: &EffortOptions,
+    ) -> Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+        let mut intervals = vec![(
+            deployment.deployment_start,
+            self.effective_end(deployment, options),
+        )];
+
+        for malfunction in options
+            .malfunctions
+            .iter()
+            .filter(|m| m.deployment_id == deployment.deployment_id)
+        {
+            intervals = intervals
+                .into_iter()
+                .flat_map(|(start, end)| {
+                    let mut remaining = Vec::new();
+                    if malfunction.start > start {
+                        remaining.push((start, end.min(malfunction.start)));
+                    }
+                    if malfunction.end < end {
+                        remaining.push((start.max(malfunction.end), end));
+                    }
+                    remaining
+                })
+                .collect();
+        }
+
+        intervals.retain(|(start, end)| start < end);
+        intervals
+    }
+
+    /// Active camera-days per deployment.
+    pub fn effort(&self, options: &EffortOptions) -> Vec<Effort> {
+        self.deployments
+            .iter()
+            .map(|deployment| {
+                let intervals = self.active_intervals(deployment, options);
+                Effort {
+                    deployment_id: deployment.deployment_id.clone(),
+                    period: None,
+                    start: intervals
+                        .first()
+                        .map(|(start, _)| *start)
+                        .unwrap_or(deployment.deployment_start),
+                    end: intervals
+                        .last()
+                        .map(|(_, end)| *end)
+                        .unwrap_or(deployment.deployment_start),
+                    camera_days: camera_days(&intervals),
+                }
+            })
+            .collect()
+    }
+
+    /// Active camera-days per deployment and period. Periods are computed in the timezone of
+    /// `deploymentStart`; periods without any activity are omitted.
+    pub fn effort_by_period(&self, period: Period, options: &EffortOptions) -> Vec<Effort> {
+        let mut effort = Vec::new();
+        for deployment in &self.deployments {
+            let intervals = self.active_intervals(deployment, options);
+            let (first, last) = match (intervals.first(), intervals.last()) {
+                (Some((first, _)), Some((_, last))) => (*first, *last),
+                _ => continue,
+            };
+            let offset = deployment.deployment_start.timezone();
+            let southern = deployment.latitude.map(|l| l < 0.0).unwrap_or(false);
+
+            let mut date = period_start(first.with_timezone(&offset).date_naive(), period);
+            while start_of_day(date, &offset) < last {
+                let next = next_period(date, period);
+                let (period_start, period_end) =
+                    (start_of_day(date, &offset), start_of_day(next, &offset));
+
+                let clipped: Vec<_> = intervals
+                    .iter()
+                    .map(|(start, end)| (*start.max(&period_start), *end.min(&period_end)))
+                    .filter(|(start, end)| start < end)
+                    .collect();
+                if let (Some((start, _)), Some((_, end))) = (clipped.first(), clipped.last()) {
+                    effort.push(Effort {
+                        deployment_id: deployment.deployment_id.clone(),
+                        period: Some(period_label(date, period, southern)),
+                        start: *start,
+                        end: *end,
+                        camera_days: camera_days(&clipped),
+                    });
+                }
+                date = next;
+            }
+        }
+
+        effort
+    }
+}
+
+fn camera_days(intervals: &[(DateTime<FixedOffset>, DateTime<FixedOffset>)]) -> f64 {
+    intervals
+        .iter()
+        .map(|(start, end)| *end - *start)
+        .fold(Duration::zero(), |total, duration| total + duration)
+        .num_seconds() as f64
+        / 86400.0
+}
+
+fn start_of_day(date: NaiveDate, offset: &FixedOffset) -> DateTime<FixedOffset> {
+    offset
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .unwrap()
+}
+
+/// First day of the period containing `date`.
+fn period_start(date: NaiveDate, period: Period) -> NaiveDate {
+    match period {
+        Period::Day => date,
+        Period::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        Period::Month => date.with_day(1).unwrap(),
+        Period::Season => {
+            let month = date.with_day(1).unwrap();
+            month - Months::new(date.month() % 3)
+        }
+    }
+}
+
+/// First day of the period following the one starting at `date`.
+fn next_period(date: NaiveDate, period: Period) -> NaiveDate {
+    match period {
+        Period::Day => date + Duration::days(1),
+        Period::Week => date + Duration::days(7),
+        Period::Month => date + Months::new(1),
+        Period::Season => date + Months::new(3),
+    }
+}
+
+fn period_label(date: NaiveDate, period: Period, southern: bool) -> String {
+    match period {
+        Period::Day => date.format("%Y-%m-%d").to_string(),
+        Period::Week => date.format("%G-W%V").to_string(),
+        Period::Month => date.format("%Y-%m").to_string(),
+        Period::Season => {
+            let seasons = if southern {
+                ["summer", "autumn", "winter", "spring"]
+            } else {
+                ["winter", "spring", "summer", "autumn"]
+            };
+            let season = seasons[(date.month() % 12 / 3) as usize];
+            format!("{}-{}", date.year(), season)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn effort_per_deployment() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let effort = package.effort(&EffortOptions::default());
+        assert_eq!(effort.len(), 4);
+
+        let first = &effort[0];
+        assert_eq!(first.deployment_id, "00a2c20d");
+        let expected = (first.end - first.start).num_seconds() as f64 / 86400.0;
+        assert!((first.camera_days - expected).abs() < 1e-9);
+        assert!(first.camera_days > 32.0 && first.camera_days < 33.0);
+    }
+
+    #[test]
+    fn malfunctions_and_inferred_end() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let deployment = package.deployment("00a2c20d").unwrap();
+        let full = package.effort(&EffortOptions::default())[0].camera_days;
+
+        let options = EffortOptions {
+            malfunctions: vec![Malfunction {
+                deployment_id: "00a2c20d".to_string(),
+                start: deployment.deployment_start + Duration::days(1),
+                end: deployment.deployment_start + Duration::days(3),
+            }],
+            infer_end_from_media: false,
+        };
+        assert_eq!(package.active_intervals(deployment, &options).len(), 2);
+        let reduced = package.effort(&options)[0].camera_days;
+        assert!((full - reduced - 2.0).abs() < 1e-9);
+
+        let inferred = package.effort(&EffortOptions {
+            infer_end_from_media: true,
+            ..Default::default()
+        })[0]
+            .camera_days;
+        assert!(inferred <= full);
+    }
+
+    #[test]
+    fn media_before_deployment_start() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let start = package.deployment("00a2c20d").unwrap().deployment_start;
+        for medium in package
+            .media
+            .iter_mut()
+            .filter(|m| m.deployment_id == "00a2c20d")
+        {
+            medium.timestamp = start - Duration::days(1);
+        }
+        let options = EffortOptions {
+            infer_end_from_media: true,
+            ..Default::default()
+        };
+
+        let deployment = package.deployment("00a2c20d").unwrap();
+        assert_eq!(package.effective_end(deployment, &options), start);
+        assert!(package.active_intervals(deployment, &options).is_empty());
+        assert_eq!(package.effort(&options)[0].camera_days, 0.0);
+    }
+
+    #[test]
+    fn effort_by_period_sums_to_total() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let total: f64 = package
+            .effort(&EffortOptions::default())
+            .iter()
+            .map(|e| e.camera_days)
+            .sum();
+
+        for period in [Period::Day, Period::Week, Period::Month, Period::Season] {
+            let by_period = package.effort_by_period(period, &EffortOptions::default());
+            let sum: f64 = by_period.iter().map(|e| e.camera_days).sum();
+            assert!((total - sum).abs() < 1e-6, "{:?}", period);
+        }
+
+        let months = package.effort_by_period(Period::Month, &EffortOptions::default());
+        let labels: Vec<_> = months
+            .iter()
+            .filter(|e| e.deployment_id == "00a2c20d")
+            .map(|e| e.period.clone().unwrap())
+            .collect();
+        assert_eq!(labels, vec!["2020-05", "2020-06", "2020-07"]);
+
+        let seasons = package.effort_by_period(Period::Season, &EffortOptions::default());
+        assert_eq!(seasons[0].period.as_deref(), Some("2020-spring"));
+    }
+}
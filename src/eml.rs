@@ -0,0 +1,753 @@
+//! Ecological Metadata Language (EML) documents, as used by the GBIF Integrated Publishing
+//! Toolkit (IPT).
+//!
+//! [`PackageMetadata::to_eml`] maps package metadata onto the GBIF EML profile (EML 2.1.1 or
+//! 2.2.0) and [`PackageMetadata::from_eml`] reads it back, so package metadata can be
+//! bootstrapped from an existing IPT resource. Contributor roles map as follows:
+//!
+//! | Camtrap DP              | EML                                                   |
+//! |-------------------------|-------------------------------------------------------|
+//! | `principalInvestigator` | `creator` and project `personnel`                     |
+//! | `contributor` (or none) | `creator`                                             |
+//! | `contact`               | `contact` and `metadataProvider`                      |
+//! | `rightsHolder`          | `associatedParty` with role `owner`                   |
+//! | `publisher`             | `associatedParty` with role `publisher`               |
+
+use crate::metadata::{Contributor, License, PackageMetadata, Project, Taxon, Temporal};
+use crate::util::escape_xml;
+use roxmltree::Node;
+
+/// Version of the EML schema (and the matching GBIF profile) to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmlVersion {
+    /// EML 2.1.1 (GBIF profile 1.2).
+    Eml211,
+
+    /// EML 2.2.0 (GBIF profile 1.3).
+    #[default]
+    Eml220,
+}
+
+impl EmlVersion {
+    fn namespace(&self) -> &'static str {
+        match self {
+            EmlVersion::Eml211 => "eml://ecoinformatics.org/eml-2.1.1",
+            EmlVersion::Eml220 => "https://eml.ecoinformatics.org/eml-2.2.0",
+        }
+    }
+
+    fn schema(&self) -> &'static str {
+        match self {
+            EmlVersion::Eml211 => "http://rs.gbif.org/schema/eml-gbif-profile/1.2/eml.xsd",
+            EmlVersion::Eml220 => "http://rs.gbif.org/schema/eml-gbif-profile/1.3/eml.xsd",
+        }
+    }
+}
+
+/// Error type for reading EML documents.
+#[derive(Debug)]
+pub enum EmlError {
+    Io(std::io::Error),
+    Xml(roxmltree::Error),
+    MissingDataset,
+}
+
+/// Licenses recognised by GBIF: Open Definition identifier, legal code URL and title.
+const LICENSES: &[(&str, &str, &str)] = &[
+    (
+        "CC0-1.0",
+        "http://creativecommons.org/publicdomain/zero/1.0/legalcode",
+        "Public Domain (CC0 1.0)",
+    ),
+    (
+        "CC-BY-4.0",
+        "http://creativecommons.org/licenses/by/4.0/legalcode",
+        "Creative Commons Attribution (CC-BY) 4.0 License",
+    ),
+    (
+        "CC-BY-NC-4.0",
+        "http://creativecommons.org/licenses/by-nc/4.0/legalcode",
+        "Creative Commons Attribution Non Commercial (CC-BY-NC) 4.0 License",
+    ),
+];
+
+/// Indented XML string builder.
+struct Writer {
+    xml: String,
+    depth: usize,
+}
+
+impl Writer {
+    fn line(&mut self, line: &str) {
+        self.xml.push_str(&"  ".repeat(self.depth));
+        self.xml.push_str(line);
+        self.xml.push('\n');
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.line(&format!("<{}>", tag));
+        self.depth += 1;
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.line(&format!("</{}>", tag));
+    }
+
+    fn element(&mut self, tag: &str, text: &str) {
+        self.line(&format!("<{0}>{1}</{0}>", tag, escape_xml(text)));
+    }
+
+    fn paragraphs(&mut self, tag: &str, text: &str) {
+        self.open(tag);
+        for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+            self.element("para", paragraph);
+        }
+        self.close(tag);
+    }
+}
+
+impl PackageMetadata {
+    /// EML document describing the package.
+    pub fn to_eml(&self, version: EmlVersion) -> String {
+        let mut w = Writer {
+            xml: "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string(),
+            depth: 0,
+        };
+        w.line(&format!(
+            "<eml:eml xmlns:eml=\"{0}\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+             xsi:schemaLocation=\"{0} {1}\" packageId=\"{2}\" system=\"http://gbif.org\" \
+             scope=\"system\" xml:lang=\"eng\">",
+            version.namespace(),
+            version.schema(),
+            escape_xml(
+                self.id
+                    .as_deref()
+                    .or(self.name.as_deref())
+                    .unwrap_or_default()
+            )
+        ));
+        w.depth += 1;
+        w.open("dataset");
+
+        if let Some(name) = &self.name {
+            w.element("alternateIdentifier", name);
+        }
+        w.element("title", self.title.as_deref().unwrap_or_default());
+
+        let creators = self.contributors_with_roles(&["principalInvestigator", "contributor", ""]);
+        for creator in &creators {
+            party(&mut w, "creator", creator, None);
+        }
+        // Principal investigators stand in for a missing contact and project personnel.
+        let investigators: Vec<&Contributor> = {
+            let investigators = self.contributors_with_roles(&["principalInvestigator"]);
+            if investigators.is_empty() {
+                creators.iter().take(1).copied().collect()
+            } else {
+                investigators
+            }
+        };
+        let contacts = {
+            let contacts = self.contributors_with_roles(&["contact"]);
+            if contacts.is_empty() {
+                investigators.clone()
+            } else {
+                contacts
+            }
+        };
+        for contact in &contacts {
+            party(&mut w, "metadataProvider", contact, None);
+        }
+        for contributor in self.contributors_with_roles(&["rightsHolder"]) {
+            party(&mut w, "associatedParty", contributor, Some("owner"));
+        }
+        for contributor in self.contributors_with_roles(&["publisher"]) {
+            party(&mut w, "associatedParty", contributor, Some("publisher"));
+        }
+
+        if let Some(created) = &self.created {
+            w.element("pubDate", created.get(..10).unwrap_or(created));
+        }
+        w.element("language", "eng");
+        if let Some(description) = &self.description {
+            w.paragraphs("abstract", description);
+        }
+        if !self.keywords.is_empty() {
+            w.open("keywordSet");
+            for keyword in &self.keywords {
+                w.element("keyword", keyword);
+            }
+            w.element("keywordThesaurus", "n/a");
+            w.close("keywordSet");
+        }
+
+        if let Some(license) = self.license("data") {
+            intellectual_rights(&mut w, license, version);
+        }
+
+        self.coverage(&mut w);
+
+        for contact in &contacts {
+            party(&mut w, "contact", contact, None);
+        }
+
+        if let Some(project) = &self.project {
+            let design = [
+                project
+                    .sampling_design
+                    .as_ref()
+                    .map(|design| format!("Sampling design: {}.", design)),
+                (!project.capture_method.is_empty())
+                    .then(|| format!("Capture method: {}.", project.capture_method.join(", "))),
+            ];
+            let design: Vec<String> = design.into_iter().flatten().collect();
+            if !design.is_empty() {
+                w.open("methods");
+                w.open("methodStep");
+                w.paragraphs("description", &design.join(" "));
+                w.close("methodStep");
+                w.close("methods");
+            }
+
+            match &project.id {
+                Some(id) => w.line(&format!("<project id=\"{}\">", escape_xml(id))),
+                None => w.line("<project>"),
+            }
+            w.depth += 1;
+            w.element("title", &project.title);
+            for person in &investigators {
+                party(&mut w, "personnel", person, Some("principalInvestigator"));
+            }
+            if let Some(description) = &project.description {
+                w.paragraphs("abstract", description);
+            }
+            w.close("project");
+        }
+        w.close("dataset");
+
+        w.open("additionalMetadata");
+        w.open("metadata");
+        w.open("gbif");
+        if let Some(created) = &self.created {
+            w.element("dateStamp", created);
+        }
+        w.element("hierarchyLevel", "dataset");
+        if let Some(citation) = &self.bibliographic_citation {
+            w.element("citation", citation);
+        }
+        w.close("gbif");
+        w.close("metadata");
+        w.close("additionalMetadata");
+
+        w.close("eml:eml");
+        w.xml
+    }
+
+    fn contributors_with_roles(&self, roles: &[&str]) -> Vec<&Contributor> {
+        self.contributors
+            .iter()
+            .filter(|c| roles.contains(&c.role.as_deref().unwrap_or_default()))
+            .collect()
+    }
+
+    /// Geographic, temporal and taxonomic coverage.
+    fn coverage(&self, w: &mut Writer) {
+        let bbox = self.spatial.as_ref().and_then(bounding_box);
+        if bbox.is_none() && self.temporal.is_none() && self.taxonomic.is_empty() {
+            return;
+        }
+        w.open("coverage");
+        if let Some((west, east, north, south)) = bbox {
+            w.open("geographicCoverage");
+            w.element(
+                "geographicDescription",
+                "Bounding box of the deployment locations",
+            );
+            w.open("boundingCoordinates");
+            w.element("westBoundingCoordinate", &west.to_string());
+            w.element("eastBoundingCoordinate", &east.to_string());
+            w.element("northBoundingCoordinate", &north.to_string());
+            w.element("southBoundingCoordinate", &south.to_string());
+            w.close("boundingCoordinates");
+            w.close("geographicCoverage");
+        }
+        if let Some(temporal) = &self.temporal {
+            w.open("temporalCoverage");
+            w.open("rangeOfDates");
+            w.open("beginDate");
+            w.element("calendarDate", &temporal.start);
+            w.close("beginDate");
+            w.open("endDate");
+            w.element("calendarDate", &temporal.end);
+            w.close("endDate");
+            w.close("rangeOfDates");
+            w.close("temporalCoverage");
+        }
+        if !self.taxonomic.is_empty() {
+            w.open("taxonomicCoverage");
+            for taxon in &self.taxonomic {
+                w.open("taxonomicClassification");
+                if let Some(rank) = &taxon.taxon_rank {
+                    w.element("taxonRankName", rank);
+                }
+                w.element("taxonRankValue", &taxon.scientific_name);
+                if let Some(name) = taxon.vernacular_names.get("eng") {
+                    w.element("commonName", name);
+                }
+                w.close("taxonomicClassification");
+            }
+            w.close("taxonomicCoverage");
+        }
+        w.close("coverage");
+    }
+}
+
+/// EML party for a contributor. Contributors without an email address or organization but
+/// with a path are assumed to be organizations, all others to be people.
+fn party(w: &mut Writer, tag: &str, contributor: &Contributor, role: Option<&str>) {
+    w.open(tag);
+    let is_organization = contributor.organization.is_none()
+        && contributor.email.is_none()
+        && contributor.path.is_some();
+    if is_organization {
+        w.element("organizationName", &contributor.title);
+    } else {
+        w.open("individualName");
+        match contributor.title.rsplit_once(' ') {
+            Some((given_name, sur_name)) => {
+                w.element("givenName", given_name);
+                w.element("surName", sur_name);
+            }
+            None => w.element("surName", &contributor.title),
+        }
+        w.close("individualName");
+        if let Some(organization) = &contributor.organization {
+            w.element("organizationName", organization);
+        }
+    }
+    if let Some(email) = &contributor.email {
+        w.element("electronicMailAddress", email);
+    }
+    if let Some(path) = &contributor.path {
+        if path.starts_with("https://orcid.org/") {
+            w.line(&format!(
+                "<userId directory=\"https://orcid.org/\">{}</userId>",
+                escape_xml(path.trim_start_matches("https://orcid.org/"))
+            ));
+        } else {
+            w.element("onlineUrl", path);
+        }
+    }
+    if let Some(role) = role {
+        w.element("role", role);
+    }
+    w.close(tag);
+}
+
+fn intellectual_rights(w: &mut Writer, license: &License, version: EmlVersion) {
+    let known = LICENSES.iter().find(|(name, url, _)| {
+        license.name.as_deref() == Some(*name) || license.path.as_deref() == Some(*url)
+    });
+    w.open("intellectualRights");
+    match known {
+        Some((name, url, title)) => {
+            let link = format!(
+                "<ulink url=\"{}\"><citetitle>{}</citetitle></ulink>",
+                url, title
+            );
+            if *name == "CC0-1.0" {
+                w.line(&format!(
+                    "<para>To the extent possible under law, the publisher has waived all rights \
+                     to these data and has dedicated them to the {}.</para>",
+                    link
+                ));
+            } else {
+                w.line(&format!(
+                    "<para>This work is licensed under a {}.</para>",
+                    link
+                ));
+            }
+        }
+        None => w.element(
+            "para",
+            license
+                .title
+                .as_deref()
+                .or(license.name.as_deref())
+                .or(license.path.as_deref())
+                .unwrap_or_default(),
+        ),
+    }
+    w.close("intellectualRights");
+
+    if version == EmlVersion::Eml220 {
+        w.open("licensed");
+        w.element(
+            "licenseName",
+            known
+                .map(|(_, _, title)| *title)
+                .or(license.title.as_deref())
+                .or(license.name.as_deref())
+                .unwrap_or_default(),
+        );
+        if let Some(url) = known.map(|(_, url, _)| *url).or(license.path.as_deref()) {
+            w.element("url", url);
+        }
+        if let Some(name) = known.map(|(name, _, _)| *name).or(license.name.as_deref()) {
+            w.element("identifier", name);
+        }
+        w.close("licensed");
+    }
+}
+
+/// Bounding box (west, east, north, south) of the coordinates of a GeoJSON geometry.
+fn bounding_box(geometry: &serde_json::Value) -> Option<(f64, f64, f64, f64)> {
+    fn positions(value: &serde_json::Value, out: &mut Vec<(f64, f64)>) {
+        if let Some(array) = value.as_array() {
+            match (
+                array.first().and_then(|v| v.as_f64()),
+                array.get(1).and_then(|v| v.as_f64()),
+            ) {
+                (Some(x), Some(y)) => out.push((x, y)),
+                _ => array.iter().for_each(|v| positions(v, out)),
+            }
+        }
+    }
+
+    let mut coordinates = Vec::new();
+    positions(geometry.get("coordinates")?, &mut coordinates);
+    let (first_x, first_y) = *coordinates.first()?;
+    Some(coordinates.iter().fold(
+        (first_x, first_x, first_y, first_y),
+        |(west, east, north, south), (x, y)| {
+            (west.min(*x), east.max(*x), north.max(*y), south.min(*y))
+        },
+    ))
+}
+
+impl PackageMetadata {
+    /// Read package metadata from an EML document, such as the `eml.xml` of an IPT resource.
+    /// Properties without an EML counterpart (e.g. `observationLevel`) are left empty.
+    pub fn from_eml(xml: &str) -> Result<Self, EmlError> {
+        let document = roxmltree::Document::parse(xml).map_err(EmlError::Xml)?;
+        let root = document.root_element();
+        let dataset = child(root, "dataset").ok_or(EmlError::MissingDataset)?;
+
+        let mut metadata = PackageMetadata {
+            id: root.attribute("packageId").map(|id| id.to_string()),
+            name: child_text(dataset, "alternateIdentifier"),
+            title: child_text(dataset, "title"),
+            created: child_text(dataset, "pubDate"),
+            description: child(dataset, "abstract").map(paragraphs),
+            keywords: children(dataset, "keywordSet")
+                .flat_map(|set| children(set, "keyword"))
+                .map(text)
+                .collect(),
+            ..Default::default()
+        };
+
+        let project = child(dataset, "project");
+        let parties = [
+            (children(dataset, "creator").collect::<Vec<_>>(), None),
+            (children(dataset, "contact").collect(), Some("contact")),
+            (children(dataset, "associatedParty").collect(), None),
+            (
+                project
+                    .map(|p| children(p, "personnel").collect())
+                    .unwrap_or_default(),
+                None,
+            ),
+        ];
+        for (nodes, role) in parties {
+            for node in nodes {
+                let role = role
+                    .map(|role| role.to_string())
+                    .or_else(|| child_text(node, "role"))
+                    .map(|role| match role.as_str() {
+                        "owner" => "rightsHolder".to_string(),
+                        "pointOfContact" => "contact".to_string(),
+                        _ => role,
+                    });
+                add_contributor(&mut metadata.contributors, contributor(node, role));
+            }
+        }
+
+        if let Some(rights) = child(dataset, "intellectualRights") {
+            metadata
+                .licenses
+                .push(read_license(rights, child(dataset, "licensed")));
+        }
+
+        if let Some(coverage) = child(dataset, "coverage") {
+            if let Some(bbox) = child(coverage, "geographicCoverage")
+                .and_then(|geographic| child(geographic, "boundingCoordinates"))
+            {
+                let coordinate =
+                    |name: &str| child_text(bbox, name).and_then(|value| value.parse::<f64>().ok());
+                if let (Some(west), Some(east), Some(north), Some(south)) = (
+                    coordinate("westBoundingCoordinate"),
+                    coordinate("eastBoundingCoordinate"),
+                    coordinate("northBoundingCoordinate"),
+                    coordinate("southBoundingCoordinate"),
+                ) {
+                    metadata.spatial = Some(serde_json::json!({
+                        "type": "Polygon",
+                        "coordinates": [[
+                            [west, south], [east, south], [east, north], [west, north], [west, south]
+                        ]]
+                    }));
+                }
+            }
+            if let Some(range) = child(coverage, "temporalCoverage")
+                .and_then(|temporal| child(temporal, "rangeOfDates"))
+            {
+                let date =
+                    |name: &str| child(range, name).and_then(|d| child_text(d, "calendarDate"));
+                if let (Some(start), Some(end)) = (date("beginDate"), date("endDate")) {
+                    metadata.temporal = Some(Temporal {
+                        start,
+                        end,
+                        ..Default::default()
+                    });
+                }
+            }
+            metadata.taxonomic = children(coverage, "taxonomicCoverage")
+                .flat_map(|taxonomic| children(taxonomic, "taxonomicClassification"))
+                .filter_map(|classification| {
+                    Some(Taxon {
+                        scientific_name: child_text(classification, "taxonRankValue")?,
+                        taxon_rank: child_text(classification, "taxonRankName")
+                            .map(|rank| rank.to_lowercase()),
+                        vernacular_names: child_text(classification, "commonName")
+                            .map(|name| [("eng".to_string(), name)].into_iter().collect())
+                            .unwrap_or_default(),
+                        ..Default::default()
+                    })
+                })
+                .collect();
+        }
+
+        if let Some(project) = project {
+            metadata.project = Some(Project {
+                id: project.attribute("id").map(|id| id.to_string()),
+                title: child_text(project, "title").unwrap_or_default(),
+                description: child(project, "abstract").map(paragraphs),
+                ..Default::default()
+            });
+        }
+
+        metadata.bibliographic_citation = root
+            .descendants()
+            .find(|node| node.tag_name().name() == "gbif")
+            .and_then(|gbif| child_text(gbif, "citation"));
+
+        Ok(metadata)
+    }
+
+    /// Read package metadata from an EML file.
+    pub fn from_eml_file(path: &str) -> Result<Self, EmlError> {
+        let xml = std::fs::read_to_string(path).map_err(EmlError::Io)?;
+        Self::from_eml(&xml)
+    }
+}
+
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn child<'a, 'input: 'a>(node: Node<'a, 'input>, name: &'a str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+/// Text content of a node, with whitespace collapsed.
+fn text(node: Node) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    child(node, name).map(text).filter(|text| !text.is_empty())
+}
+
+/// Paragraphs of a text element, separated by blank lines.
+fn paragraphs(node: Node) -> String {
+    let paragraphs: Vec<String> = children(node, "para").map(text).collect();
+    if paragraphs.is_empty() {
+        text(node)
+    } else {
+        paragraphs.join("\n\n")
+    }
+}
+
+fn contributor(node: Node, role: Option<String>) -> Contributor {
+    let individual = child(node, "individualName").map(|name| {
+        [child_text(name, "givenName"), child_text(name, "surName")]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
+    let organization = child_text(node, "organizationName");
+    let path = child(node, "userId")
+        .map(|id| {
+            format!(
+                "{}{}",
+                id.attribute("directory").unwrap_or_default(),
+                text(id)
+            )
+        })
+        .or_else(|| child_text(node, "onlineUrl"));
+    Contributor {
+        title: individual
+            .clone()
+            .or_else(|| organization.clone())
+            .unwrap_or_default(),
+        email: child_text(node, "electronicMailAddress"),
+        path,
+        role: role.or_else(|| Some("contributor".to_string())),
+        organization: individual.and(organization),
+        ..Default::default()
+    }
+}
+
+/// Role precedence when the same party appears in several EML elements.
+fn role_rank(role: Option<&str>) -> u8 {
+    match role {
+        Some("principalInvestigator") => 3,
+        Some("contact") => 2,
+        Some("contributor") | None => 0,
+        _ => 1,
+    }
+}
+
+fn add_contributor(contributors: &mut Vec<Contributor>, contributor: Contributor) {
+    match contributors
+        .iter_mut()
+        .find(|c| c.title == contributor.title)
+    {
+        Some(existing) => {
+            if role_rank(contributor.role.as_deref()) > role_rank(existing.role.as_deref()) {
+                existing.role = contributor.role;
+            }
+            existing.email = existing.email.take().or(contributor.email);
+            existing.path = existing.path.take().or(contributor.path);
+            existing.organization = existing.organization.take().or(contributor.organization);
+        }
+        None => contributors.push(contributor),
+    }
+}
+
+fn read_license(rights: Node, licensed: Option<Node>) -> License {
+    let url = rights
+        .descendants()
+        .find(|node| node.tag_name().name() == "ulink")
+        .and_then(|link| link.attribute("url"))
+        .map(|url| url.to_string())
+        .or_else(|| licensed.and_then(|l| child_text(l, "url")));
+    let known = LICENSES
+        .iter()
+        .find(|(_, known_url, _)| url.as_deref() == Some(*known_url));
+    License {
+        name: known
+            .map(|(name, _, _)| name.to_string())
+            .or_else(|| licensed.and_then(|l| child_text(l, "identifier"))),
+        path: url,
+        title: known
+            .map(|(_, _, title)| title.to_string())
+            .or_else(|| Some(paragraphs(rights)).filter(|text| !text.is_empty())),
+        scope: Some("data".to_string()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::metadata::test::example;
+
+    #[test]
+    fn eml_document() {
+        let eml = example().to_eml(EmlVersion::Eml220);
+        assert!(eml.contains("packageId=\"7cca70f5-ef8c-4f86-85fb-8f070937d7ab\""));
+        assert!(eml.contains("<surName>Desmet</surName>"));
+        assert!(
+            eml.contains("<userId directory=\"https://orcid.org/\">0000-0002-8442-8025</userId>")
+        );
+        assert!(eml.contains("<westBoundingCoordinate>4.013</westBoundingCoordinate>"));
+        assert!(eml.contains("<licensed>"));
+        assert!(roxmltree::Document::parse(&eml).is_ok());
+
+        let eml = example().to_eml(EmlVersion::Eml211);
+        assert!(eml.contains("eml://ecoinformatics.org/eml-2.1.1"));
+        assert!(!eml.contains("<licensed>"));
+    }
+
+    #[test]
+    fn eml_round_trip() {
+        let mut metadata = example();
+        metadata.title = Some("Muskrat & coypu's <camera> traps".to_string());
+        let read = PackageMetadata::from_eml(&metadata.to_eml(EmlVersion::Eml220)).unwrap();
+
+        assert_eq!(read.id, metadata.id);
+        assert_eq!(read.title, metadata.title);
+        assert_eq!(read.description, metadata.description);
+        assert_eq!(read.keywords, metadata.keywords);
+        assert_eq!(read.temporal, metadata.temporal);
+        assert_eq!(read.bibliographic_citation, metadata.bibliographic_citation);
+        assert_eq!(
+            read.license("data").unwrap().name.as_deref(),
+            Some("CC0-1.0")
+        );
+        assert_eq!(read.taxonomic[0].scientific_name, "Anas platyrhynchos");
+        assert_eq!(read.taxonomic[0].vernacular_names["eng"], "mallard");
+        assert_eq!(read.project.unwrap().id, metadata.project.unwrap().id);
+        assert_eq!(
+            read.spatial.as_ref().and_then(bounding_box),
+            metadata.spatial.as_ref().and_then(bounding_box)
+        );
+
+        let role = |title: &str| {
+            read.contributors
+                .iter()
+                .find(|c| c.title == title)
+                .and_then(|c| c.role.clone())
+        };
+        assert_eq!(
+            role("Peter Desmet").as_deref(),
+            Some("principalInvestigator")
+        );
+        assert_eq!(role("Axel Neukermans").as_deref(), Some("contributor"));
+        assert_eq!(
+            role("Research Institute for Nature and Forest (INBO)").as_deref(),
+            Some("rightsHolder")
+        );
+        let desmet = read
+            .contributors
+            .iter()
+            .find(|c| c.title == "Peter Desmet")
+            .unwrap();
+        assert_eq!(
+            desmet.path.as_deref(),
+            Some("https://orcid.org/0000-0002-8442-8025")
+        );
+    }
+
+    #[test]
+    fn eml_without_dataset() {
+        assert!(matches!(
+            PackageMetadata::from_eml("<eml:eml xmlns:eml=\"x\"/>"),
+            Err(EmlError::MissingDataset)
+        ));
+    }
+}
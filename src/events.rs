@@ -0,0 +1,188 @@
+//! Reconstruction of events (sequences) from media timestamps.
+//!
+//! Media files of a deployment are grouped into the same event as long as the time between
+//! consecutive media files does not exceed a configurable gap.
+
+use crate::{Medium, ObservationLevel, Package};
+use chrono::{DateTime, Duration, FixedOffset};
+use std::collections::HashMap;
+
+/// A sequence of media files recorded in quick succession during a deployment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// Identifier of the event, unique within the package.
+    pub event_id: String,
+
+    /// Identifier of the deployment the event belongs to.
+    pub deployment_id: String,
+
+    /// Timestamp of the first media file of the event.
+    pub event_start: DateTime<FixedOffset>,
+
+    /// Timestamp of the last media file of the event.
+    pub event_end: DateTime<FixedOffset>,
+
+    /// Identifiers of the media files of the event, in chronological order.
+    pub media_ids: Vec<String>,
+}
+
+/// Group media files into events per deployment. A new event starts whenever the time since
+/// the previous media file of the same deployment exceeds `gap`.
+///
+/// Media files are ordered on timestamp (first) and fileName (second). Events are identified
+/// as `<deploymentID>_<n>`, numbered from 1 in chronological order within each deployment.
+pub fn group_media(media: &[Medium], gap: Duration) -> Vec<Event> {
+    let mut by_deployment: Vec<(&str, Vec<&Medium>)> = Vec::new();
+    for medium in media {
+        match by_deployment
+            .iter_mut()
+            .find(|(deployment_id, _)| *deployment_id == medium.deployment_id)
+        {
+            Some((_, media)) => media.push(medium),
+            None => by_deployment.push((&medium.deployment_id, vec![medium])),
+        }
+    }
+
+    let mut events = Vec::new();
+    for (deployment_id, mut media) in by_deployment {
+        media.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.file_name.cmp(&b.file_name))
+        });
+
+        let mut current: Option<Event> = None;
+        let mut n = 0;
+        for medium in media {
+            if let Some(event) = current.as_mut() {
+                if medium.timestamp - event.event_end <= gap {
+                    event.event_end = medium.timestamp;
+                    event.media_ids.push(medium.media_id.clone());
+                    continue;
+                }
+                events.push(current.take().unwrap());
+            }
+
+            n += 1;
+            current = Some(Event {
+                event_id: format!("{}_{}", deployment_id, n),
+                deployment_id: deployment_id.to_string(),
+                event_start: medium.timestamp,
+                event_end: medium.timestamp,
+                media_ids: vec![medium.media_id.clone()],
+            });
+        }
+        events.extend(current);
+    }
+
+    events
+}
+
+impl Package {
+    /// Reconstruct events from the media timestamps using the given gap and assign `eventID`,
+    /// `eventStart` and `eventEnd` to the observations.
+    ///
+    /// Media-level observations get the event of their media file. Event-level observations
+    /// are moved to the new event that the media-level observations sharing their previous
+    /// `eventID` were assigned to, so the package stays consistent. Event-level observations
+    /// that cannot be linked this way are left untouched.
+    pub fn assign_events(&mut self, gap: Duration) -> Vec<Event> {
+        let events = group_media(&self.media, gap);
+
+        let mut event_by_media: HashMap<&str, &Event> = HashMap::new();
+        for event in &events {
+            for media_id in &event.media_ids {
+                event_by_media.insert(media_id, event);
+            }
+        }
+
+        let mut event_by_previous_id: HashMap<String, &Event> = HashMap::new();
+        for observation in self
+            .observations
+            .iter_mut()
+            .filter(|o| o.observation_level == ObservationLevel::Media)
+        {
+            let event = match observation
+                .media_id
+                .as_deref()
+                .and_then(|media_id| event_by_media.get(media_id))
+            {
+                Some(event) => *event,
+                None => continue,
+            };
+
+            if let Some(previous) = observation.event_id.take() {
+                event_by_previous_id.entry(previous).or_insert(event);
+            }
+            observation.event_id = Some(event.event_id.clone());
+            observation.event_start = Some(event.event_start);
+            observation.event_end = Some(event.event_end);
+        }
+
+        for observation in self
+            .observations
+            .iter_mut()
+            .filter(|o| o.observation_level == ObservationLevel::Event)
+        {
+            let event = match observation
+                .event_id
+                .as_ref()
+                .and_then(|event_id| event_by_previous_id.get(event_id))
+            {
+                Some(event) => *event,
+                None => continue,
+            };
+
+            observation.event_id = Some(event.event_id.clone());
+            observation.event_start = Some(event.event_start);
+            observation.event_end = Some(event.event_end);
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ReadDataPackageCsv;
+
+    #[test]
+    fn group_fixture_media() {
+        let media = Medium::from_file("fixtures/media.csv").unwrap();
+        let events = group_media(&media, Duration::seconds(60));
+        assert_eq!(events.len(), 34);
+        assert_eq!(
+            events.iter().map(|e| e.media_ids.len()).sum::<usize>(),
+            media.len()
+        );
+
+        let first = &events[0];
+        assert_eq!(first.event_id, "00a2c20d_1");
+        assert!(first.event_start <= first.event_end);
+    }
+
+    #[test]
+    fn larger_gap_merges_events() {
+        let media = Medium::from_file("fixtures/media.csv").unwrap();
+        let short = group_media(&media, Duration::seconds(60));
+        let long = group_media(&media, Duration::days(1));
+        assert!(long.len() < short.len());
+    }
+
+    #[test]
+    fn assign_events_keeps_package_consistent() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let events = package.assign_events(Duration::seconds(60));
+
+        for observation in &package.observations {
+            let event_id = observation.event_id.as_ref().unwrap();
+            let event = events.iter().find(|e| &e.event_id == event_id).unwrap();
+            assert_eq!(observation.event_start, Some(event.event_start));
+            assert_eq!(observation.event_end, Some(event.event_end));
+            if let Some(media_id) = &observation.media_id {
+                assert!(event.media_ids.contains(media_id));
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Filtering of observations into independent detections.
+//!
+//! Occupancy and relative abundance analyses usually only consider detections of the same
+//! species at the same site that are separated by a threshold (commonly 30 minutes).
+
+use crate::{Observation, ObservationLevel, ObservationType, Package, WriteDataPackageCsv};
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Site at which detections are grouped before applying the threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Group per deployment.
+    Deployment,
+
+    /// Group per location (locationID), falling back to the deployment for deployments
+    /// without a location.
+    Location,
+}
+
+/// Timestamp used for an observation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampSource {
+    /// `eventStart` of the observation.
+    EventStart,
+
+    /// `timestamp` of the media file the observation is derived from. Only applicable for
+    /// media-based observations.
+    Media,
+}
+
+/// Record the threshold is measured from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparedTo {
+    /// Previous independent detection.
+    LastIndependent,
+
+    /// Previous observation, whether or not it was independent.
+    LastRecord,
+}
+
+/// Options for [`Package::independent_detections`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndependenceOptions {
+    /// Minimum time between independent detections of the same species at the same site.
+    pub threshold: Duration,
+
+    /// Site at which detections are grouped.
+    pub group_by: GroupBy,
+
+    /// Timestamp used for each observation.
+    pub timestamp_source: TimestampSource,
+
+    /// Record the threshold is measured from.
+    pub compared_to: ComparedTo,
+
+    /// Level of the observations to consider.
+    pub observation_level: ObservationLevel,
+}
+
+impl Default for IndependenceOptions {
+    fn default() -> Self {
+        IndependenceOptions {
+            threshold: Duration::minutes(30),
+            group_by: GroupBy::Deployment,
+            timestamp_source: TimestampSource::EventStart,
+            compared_to: ComparedTo::LastIndependent,
+            observation_level: ObservationLevel::Event,
+        }
+    }
+}
+
+/// Independent detection of a species at a site.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IndependentDetection {
+    /// Identifier of the deployment of the first observation of the detection.
+    #[serde(rename = "deploymentID")]
+    pub deployment_id: String,
+
+    /// Identifier of the deployment location.
+    #[serde(rename = "locationID")]
+    pub location_id: Option<String>,
+
+    /// Scientific name of the detected species.
+    #[serde(rename = "scientificName")]
+    pub scientific_name: String,
+
+    /// Timestamp of the first observation of the detection.
+    pub timestamp: DateTime<FixedOffset>,
+
+    /// Highest number of individuals among the observations of the detection.
+    pub count: Option<u32>,
+
+    /// Identifier of the first observation of the detection.
+    #[serde(rename = "observationID")]
+    pub observation_id: String,
+
+    /// Number of observations merged into the detection.
+    pub records: usize,
+}
+
+impl WriteDataPackageCsv<IndependentDetection> for Vec<IndependentDetection> {}
+
+impl Package {
+    /// Independent detections of animal observations with a scientific name, ordered by site,
+    /// species and timestamp.
+    ///
+    /// Observations that are less than the threshold apart from the previous detection (or
+    /// record) of the same species at the same site are merged into that detection.
+    /// Observations without a timestamp for the chosen source are skipped.
+    pub fn independent_detections(
+        &self,
+        options: &IndependenceOptions,
+    ) -> Vec<IndependentDetection> {
+        let location_by_deployment: HashMap<&str, Option<&str>> = self
+            .deployments
+            .iter()
+            .map(|d| (d.deployment_id.as_str(), d.location_id.as_deref()))
+            .collect();
+        let timestamp_by_media: HashMap<&str, DateTime<FixedOffset>> = self
+            .media
+            .iter()
+            .map(|m| (m.media_id.as_str(), m.timestamp))
+            .collect();
+
+        let mut records: Vec<(String, &str, DateTime<FixedOffset>, &Observation)> = self
+            .observations
+            .iter()
+            .filter(|o| o.observation_level == options.observation_level)
+            .filter(|o| o.observation_type == ObservationType::Animal)
+            .filter_map(|o| {
+                let scientific_name = o.scientific_name.as_deref()?;
+                let timestamp = match options.timestamp_source {
+                    TimestampSource::EventStart => o.event_start?,
+                    TimestampSource::Media => *timestamp_by_media.get(o.media_id.as_deref()?)?,
+                };
+                let location_id = location_by_deployment
+                    .get(o.deployment_id.as_str())
+                    .copied()
+                    .flatten();
+                let site = match (options.group_by, location_id) {
+                    (GroupBy::Location, Some(location_id)) => format!("location:{}", location_id),
+                    _ => format!("deployment:{}", o.deployment_id),
+                };
+                Some((site, scientific_name, timestamp, o))
+            })
+            .collect();
+        records.sort_by(|a, b| (&a.0, a.1, a.2).cmp(&(&b.0, b.1, b.2)));
+
+        let mut detections: Vec<IndependentDetection> = Vec::new();
+        let mut previous: Option<(&str, &str, DateTime<FixedOffset>)> = None;
+        for (site, scientific_name, timestamp, observation) in &records {
+            let dependent = match previous {
+                Some((previous_site, previous_name, previous_timestamp)) => {
+                    previous_site == site
+                        && previous_name == *scientific_name
+                        && *timestamp - previous_timestamp < options.threshold
+                }
+                None => false,
+            };
+
+            if dependent {
+                let detection = detections.last_mut().unwrap();
+                detection.records += 1;
+                detection.count = detection.count.max(observation.count);
+                if options.compared_to == ComparedTo::LastRecord {
+                    previous = Some((site, scientific_name, *timestamp));
+                }
+                continue;
+            }
+
+            detections.push(IndependentDetection {
+                deployment_id: observation.deployment_id.clone(),
+                location_id: location_by_deployment
+                    .get(observation.deployment_id.as_str())
+                    .copied()
+                    .flatten()
+                    .map(|location_id| location_id.to_string()),
+                scientific_name: scientific_name.to_string(),
+                timestamp: *timestamp,
+                count: observation.count,
+                observation_id: observation.observation_id.clone(),
+                records: 1,
+            });
+            previous = Some((site, scientific_name, *timestamp));
+        }
+
+        detections
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn independent_event_detections() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let all = package.independent_detections(&IndependenceOptions {
+            threshold: Duration::zero(),
+            ..Default::default()
+        });
+        let independent = package.independent_detections(&IndependenceOptions::default());
+
+        assert_eq!(all.iter().map(|d| d.records).sum::<usize>(), all.len());
+        assert!(independent.len() <= all.len());
+        assert_eq!(
+            independent.iter().map(|d| d.records).sum::<usize>(),
+            all.len()
+        );
+
+        for pair in independent.windows(2) {
+            if pair[0].deployment_id == pair[1].deployment_id
+                && pair[0].scientific_name == pair[1].scientific_name
+            {
+                assert!(pair[1].timestamp - pair[0].timestamp >= Duration::minutes(30));
+            }
+        }
+    }
+
+    #[test]
+    fn independent_media_detections() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let detections = package.independent_detections(&IndependenceOptions {
+            timestamp_source: TimestampSource::Media,
+            observation_level: ObservationLevel::Media,
+            compared_to: ComparedTo::LastRecord,
+            group_by: GroupBy::Location,
+            ..Default::default()
+        });
+        assert!(!detections.is_empty());
+        assert!(detections.iter().all(|d| d.location_id.is_some()));
+    }
+}
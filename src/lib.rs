@@ -6,6 +6,42 @@
 use bytes::Buf;
 use serde::{Deserialize, Serialize};
 
+pub mod accumulation;
+pub mod activity;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod camtrapr;
+pub mod coco;
+pub mod consolidate;
+pub mod correction;
+pub mod diagnostics;
+pub mod distance;
+pub mod dwca;
+pub mod effort;
+pub mod eml;
+pub mod events;
+pub mod independence;
+pub mod megadetector;
+pub mod metadata;
+pub mod occupancy;
+pub mod package;
+pub mod rai;
+pub mod rem;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod summary;
+pub mod sun;
+pub mod tags;
+pub mod timelapse;
+pub mod timezone;
+mod util;
+pub mod wildlife_insights;
+
+pub use events::Event;
+pub use metadata::PackageMetadata;
+pub use package::{Package, PackageError};
+pub use tags::Tag;
+
 /// Camera trap placement (deployment).
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Deployment {
@@ -24,6 +60,9 @@ pub struct Deployment {
     /// Latitude of the deployment location in decimal degrees, using the WGS84 datum.
     pub latitude: Option<f64>,
 
+    /// Longitude of the deployment location in decimal degrees, using the WGS84 datum.
+    pub longitude: Option<f64>,
+
     /// Horizontal distance from the given latitude and longitude describing the smallest circle containing the deployment location. Expressed in meters. Especially relevant when coordinates are rounded to protect sensitive species.
     #[serde(rename = "locationRadius")]
     pub location_radius: Option<f64>,
@@ -99,7 +138,7 @@ pub struct Deployment {
 }
 
 /// Type of the feature (if any) associated with the deployment.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FeatureType {
     #[serde(rename = "roadPaved")]
     RoadPaved,
@@ -190,7 +229,7 @@ pub struct Medium {
 }
 
 /// Method used to capture the media file.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CaptureMethod {
     #[serde(rename = "activityDetection")]
     ActivityDetection,
@@ -313,7 +352,7 @@ pub struct Observation {
 }
 
 /// Level at which the observation was classified.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ObservationLevel {
     #[serde(rename = "media")]
     Media,
@@ -323,7 +362,7 @@ pub enum ObservationLevel {
 }
 
 /// Type of the observation.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ObservationType {
     #[serde(rename = "animal")]
     Animal,
@@ -345,7 +384,7 @@ pub enum ObservationType {
 }
 
 /// Type of the camera setup action associated with the observation.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CameraSetupType {
     #[serde(rename = "setup")]
     Setup,
@@ -355,7 +394,7 @@ pub enum CameraSetupType {
 }
 
 /// Life stage of the observed individual(s).
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum LifeStage {
     #[serde(rename = "adult")]
     Adult,
@@ -368,7 +407,7 @@ pub enum LifeStage {
 }
 
 /// Sex of the observed individual(s)
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Sex {
     #[serde(rename = "female")]
     Female,
@@ -378,7 +417,7 @@ pub enum Sex {
 }
 
 /// Classification method used to classify the observation.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ClassificationMethod {
     #[serde(rename = "human")]
     Human,
@@ -0,0 +1,437 @@
+//! Import of MegaDetector batch output.
+//!
+//! MegaDetector writes its results as a JSON file listing, per image, the detections with a
+//! category (`animal`, `person` or `vehicle`), a confidence and a bounding box. The bounding
+//! box is relative to the image size with its origin at the top-left corner, as in Camtrap DP.
+//! See <https://lila.science/megadetector-output-format>.
+
+use crate::{
+    ClassificationMethod, Medium, Observation, ObservationLevel, ObservationType, Package,
+    PackageError,
+};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+/// MegaDetector batch output.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct MegaDetectorOutput {
+    /// Processed images.
+    pub images: Vec<MegaDetectorImage>,
+
+    /// Detection category names, keyed by category identifier (e.g. `"1": "animal"`).
+    #[serde(default)]
+    pub detection_categories: HashMap<String, String>,
+
+    /// Information about the detector run.
+    #[serde(default)]
+    pub info: MegaDetectorInfo,
+}
+
+/// Information about the detector run.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct MegaDetectorInfo {
+    /// Detector model file (e.g. `md_v5a.0.0.pt`).
+    pub detector: Option<String>,
+
+    /// Time at which detection completed (e.g. `2023-02-01 10:15:00`).
+    pub detection_completion_time: Option<String>,
+
+    /// Version of the output format.
+    pub format_version: Option<String>,
+}
+
+/// Result for a single image.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct MegaDetectorImage {
+    /// Path of the image, relative to the folder that was processed.
+    pub file: String,
+
+    /// Detections. Absent if the image could not be processed.
+    pub detections: Option<Vec<MegaDetectorDetection>>,
+
+    /// Reason the image could not be processed.
+    pub failure: Option<String>,
+}
+
+/// Single detection.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct MegaDetectorDetection {
+    /// Detection category identifier.
+    pub category: String,
+
+    /// Confidence of the detection (0-1).
+    pub conf: f64,
+
+    /// Bounding box as `[x, y, width, height]`, relative to the image size.
+    pub bbox: Option<[f64; 4]>,
+}
+
+/// Options for [`Package::import_megadetector`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MegaDetectorOptions {
+    /// Detections with a lower confidence are ignored.
+    pub threshold: f64,
+
+    /// If true, images without detections at or above the threshold get a `blank`
+    /// observation. Otherwise they get no observation.
+    pub blanks: bool,
+
+    /// Value for `classifiedBy`. Defaults to `MegaDetector` followed by the detector model.
+    pub classified_by: Option<String>,
+}
+
+impl Default for MegaDetectorOptions {
+    fn default() -> Self {
+        MegaDetectorOptions {
+            threshold: 0.2,
+            blanks: true,
+            classified_by: None,
+        }
+    }
+}
+
+/// Outcome of [`Package::import_megadetector`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MegaDetectorImport {
+    /// Number of observations added.
+    pub observations: usize,
+
+    /// Number of observations of an earlier import that were replaced.
+    pub replaced: usize,
+
+    /// Images that could not be matched to a media file.
+    pub unmatched: Vec<String>,
+
+    /// Images that MegaDetector failed to process.
+    pub failed: Vec<String>,
+}
+
+impl MegaDetectorOutput {
+    /// Read MegaDetector batch output from a JSON file.
+    pub fn from_file(path: &str) -> Result<Self, PackageError> {
+        let file = std::fs::File::open(path).map_err(PackageError::Io)?;
+        serde_json::from_reader(std::io::BufReader::new(file)).map_err(PackageError::Json)
+    }
+
+    fn classified_by(&self) -> String {
+        match &self.info.detector {
+            Some(detector) => format!(
+                "MegaDetector {}",
+                detector.trim_end_matches(".pt").trim_end_matches(".pb")
+            ),
+            None => "MegaDetector".to_string(),
+        }
+    }
+
+    fn observation_type(&self, category: &str) -> ObservationType {
+        match self
+            .detection_categories
+            .get(category)
+            .map(|name| name.as_str())
+            .unwrap_or(category)
+        {
+            "animal" | "1" => ObservationType::Animal,
+            "person" | "2" => ObservationType::Human,
+            "vehicle" | "3" => ObservationType::Vehicle,
+            _ => ObservationType::Unknown,
+        }
+    }
+}
+
+/// Index of media files by path and file name, to match the image paths of MegaDetector.
+struct MediaIndex<'a> {
+    by_path: HashMap<String, &'a Medium>,
+    by_name: HashMap<String, Vec<&'a Medium>>,
+}
+
+impl<'a> MediaIndex<'a> {
+    fn new(media: &'a [Medium]) -> Self {
+        let mut index = MediaIndex {
+            by_path: HashMap::new(),
+            by_name: HashMap::new(),
+        };
+        for medium in media {
+            let path = normalize(&medium.file_path);
+            let name = medium
+                .file_name
+                .clone()
+                .unwrap_or_else(|| file_name(&path).to_string());
+            index.by_path.insert(path, medium);
+            index.by_name.entry(name).or_default().push(medium);
+        }
+        index
+    }
+
+    /// Media file of an image: the media file with the same path, else the only media file
+    /// whose path ends with the image path, else the only media file with the same file name
+    /// (preferring those whose deploymentID is a directory of the image path).
+    fn find(&self, file: &str) -> Option<&'a Medium> {
+        let file = normalize(file);
+        if let Some(medium) = self.by_path.get(&file) {
+            return Some(medium);
+        }
+        let suffix = format!("/{}", file);
+        let mut matches = self
+            .by_path
+            .iter()
+            .filter(|(path, _)| path.ends_with(&suffix));
+        if let (Some((_, medium)), None) = (matches.next(), matches.next()) {
+            return Some(medium);
+        }
+
+        let candidates = self.by_name.get(file_name(&file))?;
+        if candidates.len() == 1 {
+            return Some(candidates[0]);
+        }
+        let directories: Vec<&str> = file.split('/').collect();
+        let mut matches = candidates
+            .iter()
+            .filter(|medium| directories.contains(&medium.deployment_id.as_str()));
+        match (matches.next(), matches.next()) {
+            (Some(medium), None) => Some(medium),
+            _ => None,
+        }
+    }
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Parse the detection completion time, which MegaDetector writes without a UTC offset. The
+/// offset of the media file is assumed.
+fn completion_time(time: Option<&str>, offset: &FixedOffset) -> Option<DateTime<FixedOffset>> {
+    let time = time?;
+    DateTime::parse_from_rfc3339(time).ok().or_else(|| {
+        NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .and_then(|naive| offset.from_local_datetime(&naive).single())
+    })
+}
+
+/// Whether an observation was added by [`Package::import_megadetector`].
+fn is_imported(observation: &Observation, media_id: &str) -> bool {
+    observation
+        .observation_id
+        .strip_prefix(media_id)
+        .and_then(|id| id.strip_prefix("_md"))
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl Package {
+    /// Add media-based observations from MegaDetector output. Every detection at or above the
+    /// confidence threshold becomes an observation of type `animal`, `human` or `vehicle`
+    /// with its bounding box and confidence. Observation IDs are `<mediaID>_md<n>`; observations
+    /// of an earlier import for the same media files are replaced.
+    pub fn import_megadetector(
+        &mut self,
+        output: &MegaDetectorOutput,
+        options: &MegaDetectorOptions,
+    ) -> MegaDetectorImport {
+        let classified_by = options
+            .classified_by
+            .clone()
+            .unwrap_or_else(|| output.classified_by());
+        let index = MediaIndex::new(&self.media);
+        let mut import = MegaDetectorImport::default();
+        let mut observations = Vec::new();
+        let mut imported: HashSet<String> = HashSet::new();
+
+        for image in &output.images {
+            let detections = match (&image.detections, &image.failure) {
+                (Some(detections), None) => detections,
+                _ => {
+                    import.failed.push(image.file.clone());
+                    continue;
+                }
+            };
+            let medium = match index.find(&image.file) {
+                Some(medium) => medium,
+                None => {
+                    import.unmatched.push(image.file.clone());
+                    continue;
+                }
+            };
+
+            imported.insert(medium.media_id.clone());
+
+            let observation = |n: usize, observation_type: ObservationType| Observation {
+                observation_id: format!("{}_md{}", medium.media_id, n),
+                deployment_id: medium.deployment_id.clone(),
+                media_id: Some(medium.media_id.clone()),
+                event_id: None,
+                event_start: Some(medium.timestamp),
+                event_end: Some(medium.timestamp),
+                observation_level: ObservationLevel::Media,
+                observation_type,
+                camera_setup_type: None,
+                scientific_name: None,
+                count: None,
+                life_stage: None,
+                sex: None,
+                behavior: None,
+                individual_id: None,
+                individual_position_radius: None,
+                individual_position_angle: None,
+                individual_speed: None,
+                bbox_x: None,
+                bbox_y: None,
+                bbox_width: None,
+                bbox_height: None,
+                classification_method: Some(ClassificationMethod::Machine),
+                classified_by: Some(classified_by.clone()),
+                classification_timestamp: completion_time(
+                    output.info.detection_completion_time.as_deref(),
+                    medium.timestamp.offset(),
+                ),
+                classification_probability: None,
+                observation_tags: None,
+                observation_comments: None,
+            };
+
+            let accepted: Vec<&MegaDetectorDetection> = detections
+                .iter()
+                .filter(|d| d.conf >= options.threshold)
+                .collect();
+            for (n, detection) in accepted.iter().enumerate() {
+                let mut o = observation(n + 1, output.observation_type(&detection.category));
+                o.count = Some(1);
+                o.classification_probability = Some(detection.conf);
+                if let Some([x, y, width, height]) = detection.bbox {
+                    o.bbox_x = Some(x);
+                    o.bbox_y = Some(y);
+                    o.bbox_width = Some(width);
+                    o.bbox_height = Some(height);
+                }
+                observations.push(o);
+            }
+            if accepted.is_empty() && options.blanks {
+                // The probability of a blank is the complement of the most confident
+                // (rejected) detection.
+                let max_conf = detections.iter().map(|d| d.conf).fold(0.0, f64::max);
+                let mut o = observation(1, ObservationType::Blank);
+                o.classification_probability = Some(1.0 - max_conf);
+                observations.push(o);
+            }
+        }
+
+        let before = self.observations.len();
+        self.observations.retain(|o| {
+            !o.media_id
+                .as_ref()
+                .is_some_and(|media_id| imported.contains(media_id) && is_imported(o, media_id))
+        });
+        import.replaced = before - self.observations.len();
+        import.observations = observations.len();
+        self.observations.extend(observations);
+        import
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn output() -> MegaDetectorOutput {
+        serde_json::from_str(
+            r#"{
+                "images": [
+                    {"file": "00a2c20d\\20200709093328-RCNX0001.JPG", "max_detection_conf": 0.93, "detections": [
+                        {"category": "1", "conf": 0.93, "bbox": [0.1, 0.2, 0.3, 0.4]},
+                        {"category": "2", "conf": 0.5, "bbox": [0.5, 0.5, 0.1, 0.2]},
+                        {"category": "1", "conf": 0.05, "bbox": [0.0, 0.0, 0.1, 0.1]}
+                    ]},
+                    {"file": "00a2c20d/20200709093328-RCNX0002.JPG", "max_detection_conf": 0.1, "detections": [
+                        {"category": "1", "conf": 0.1, "bbox": [0.1, 0.2, 0.3, 0.4]}
+                    ]},
+                    {"file": "elsewhere/IMG_0001.JPG", "detections": []},
+                    {"file": "00a2c20d/corrupt.JPG", "failure": "Failure image access"}
+                ],
+                "detection_categories": {"1": "animal", "2": "person", "3": "vehicle"},
+                "info": {"detector": "md_v5a.0.0.pt", "detection_completion_time": "2023-02-01 10:15:00", "format_version": "1.3"}
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn import_megadetector() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let before = package.observations.len();
+        let import = package.import_megadetector(&output(), &MegaDetectorOptions::default());
+
+        assert_eq!(import.observations, 3);
+        assert_eq!(import.unmatched, vec!["elsewhere/IMG_0001.JPG"]);
+        assert_eq!(import.failed, vec!["00a2c20d/corrupt.JPG"]);
+        assert_eq!(package.observations.len(), before + 3);
+
+        let animal = package
+            .observations
+            .iter()
+            .find(|o| o.observation_id == "07840dcc_md1")
+            .unwrap();
+        assert_eq!(animal.observation_type, ObservationType::Animal);
+        assert_eq!(animal.bbox_width, Some(0.3));
+        assert_eq!(animal.classification_probability, Some(0.93));
+        assert_eq!(
+            animal.classified_by.as_deref(),
+            Some("MegaDetector md_v5a.0.0")
+        );
+        assert_eq!(
+            animal.classification_timestamp.unwrap().to_rfc3339(),
+            "2023-02-01T10:15:00+02:00"
+        );
+
+        let blank = package
+            .observations
+            .iter()
+            .find(|o| o.observation_id == "401386c7_md1")
+            .unwrap();
+        assert_eq!(blank.observation_type, ObservationType::Blank);
+        assert_eq!(blank.classification_probability, Some(0.9));
+    }
+
+    #[test]
+    fn import_megadetector_without_blanks() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let import = package.import_megadetector(
+            &output(),
+            &MegaDetectorOptions {
+                threshold: 0.8,
+                blanks: false,
+                classified_by: Some("MDv5a".to_string()),
+            },
+        );
+        assert_eq!(import.observations, 1);
+    }
+
+    #[test]
+    fn import_megadetector_twice() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        let before = package.observations.len();
+        package.import_megadetector(&output(), &MegaDetectorOptions::default());
+        let import = package.import_megadetector(
+            &output(),
+            &MegaDetectorOptions {
+                threshold: 0.8,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(import.replaced, 3);
+        assert_eq!(import.observations, 2);
+        assert_eq!(package.observations.len(), before + 2);
+        let ids: HashSet<&str> = package
+            .observations
+            .iter()
+            .map(|o| o.observation_id.as_str())
+            .collect();
+        assert_eq!(ids.len(), package.observations.len());
+        assert!(!ids.contains("07840dcc_md2"));
+    }
+}
@@ -0,0 +1,356 @@
+//! Package metadata, as described in `datapackage.json`.
+//!
+//! Only the properties defined by the Camera Trap Data Package profile are typed; any other
+//! property, at the top level or in a contributor, license, project, temporal coverage or
+//! taxon, is kept in an `other` map so the descriptor round-trips.
+
+use serde::{Deserialize, Serialize};
+
+/// Metadata of a Camera Trap Data Package (`datapackage.json`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PackageMetadata {
+    /// Identifier of the package, unique within its context.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Globally unique identifier of the package (e.g. a DOI or UUID).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Date and time at which the package was created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+
+    /// Title of the package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// People and organizations who contributed to the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contributors: Vec<Contributor>,
+
+    /// Description of the package. May contain Markdown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Version of the package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Keywords describing the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+
+    /// Home on the web of the package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+
+    /// Licenses of the package. Licenses with scope `data` apply to the data, licenses with
+    /// scope `media` to the media files.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<License>,
+
+    /// Bibliographic citation of the package.
+    #[serde(
+        rename = "bibliographicCitation",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub bibliographic_citation: Option<String>,
+
+    /// Project the package originates from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<Project>,
+
+    /// Least precise coordinate precision of the deployment coordinates, in decimal degrees.
+    #[serde(
+        rename = "coordinatePrecision",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub coordinate_precision: Option<f64>,
+
+    /// Spatial coverage of the package, as a GeoJSON geometry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spatial: Option<serde_json::Value>,
+
+    /// Temporal coverage of the package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temporal: Option<Temporal>,
+
+    /// Taxonomic coverage of the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub taxonomic: Vec<Taxon>,
+
+    /// Other properties of the descriptor (e.g. `resources`, `profile`, `sources`).
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Person or organization who contributed to the package.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Contributor {
+    /// Name of the person or organization.
+    pub title: String,
+
+    /// Email address of the contributor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+
+    /// URL of the contributor (e.g. an ORCID).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Role of the contributor (e.g. `contact`, `principalInvestigator`, `rightsHolder`,
+    /// `publisher`, `contributor`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+
+    /// Organization the contributor is affiliated with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+
+    /// Other properties of the contributor (e.g. `firstName` and `lastName`).
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// License of the package.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct License {
+    /// Open Definition license identifier (e.g. `CC0-1.0`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// URL of the license.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Human readable title of the license.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Scope of the license: `data` or `media`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+
+    /// Other properties of the license.
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Project the package originates from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Project {
+    /// Unique identifier of the project.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Title of the project.
+    pub title: String,
+
+    /// Project acronym.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acronym: Option<String>,
+
+    /// Description of the project.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Project website.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Type of the sampling design (e.g. `simpleRandom`, `systematicRandom`, `targeted`).
+    #[serde(rename = "samplingDesign", skip_serializing_if = "Option::is_none")]
+    pub sampling_design: Option<String>,
+
+    /// Method(s) used to capture the media files.
+    #[serde(
+        rename = "captureMethod",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub capture_method: Vec<String>,
+
+    /// true if the project includes marked or recognizable individuals.
+    #[serde(rename = "individualAnimals", skip_serializing_if = "Option::is_none")]
+    pub individual_animals: Option<bool>,
+
+    /// Level(s) at which observations are provided.
+    #[serde(
+        rename = "observationLevel",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub observation_level: Vec<String>,
+
+    /// Other properties of the project.
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Temporal coverage of the package.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Temporal {
+    /// Start date (YYYY-MM-DD).
+    pub start: String,
+
+    /// End date (YYYY-MM-DD).
+    pub end: String,
+
+    /// Other properties of the temporal coverage.
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Taxon observed in the package.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Taxon {
+    /// Scientific name of the taxon, as used in `observations.scientificName`.
+    #[serde(rename = "scientificName")]
+    pub scientific_name: String,
+
+    /// Unique identifier of the taxon (e.g. a URI).
+    #[serde(rename = "taxonID", skip_serializing_if = "Option::is_none")]
+    pub taxon_id: Option<String>,
+
+    /// Taxonomic rank of the scientific name (e.g. `species`).
+    #[serde(rename = "taxonRank", skip_serializing_if = "Option::is_none")]
+    pub taxon_rank: Option<String>,
+
+    /// Common names of the taxon, keyed by ISO 639-2 language code.
+    #[serde(
+        rename = "vernacularNames",
+        default,
+        skip_serializing_if = "std::collections::BTreeMap::is_empty"
+    )]
+    pub vernacular_names: std::collections::BTreeMap<String, String>,
+
+    /// Other properties of the taxon (e.g. `order` and `family`).
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl PackageMetadata {
+    /// Read metadata from a `datapackage.json` file.
+    pub fn from_file(path: &str) -> Result<Self, crate::PackageError> {
+        let file = std::fs::File::open(path).map_err(crate::PackageError::Io)?;
+        serde_json::from_reader(std::io::BufReader::new(file)).map_err(crate::PackageError::Json)
+    }
+
+    /// Write metadata to a `datapackage.json` file.
+    pub fn to_file(&self, path: &str) -> Result<(), crate::PackageError> {
+        let file = std::fs::File::create(path).map_err(crate::PackageError::Io)?;
+        serde_json::to_writer_pretty(file, self).map_err(crate::PackageError::Json)
+    }
+
+    /// Taxon with the given scientific name.
+    pub fn taxon(&self, scientific_name: &str) -> Option<&Taxon> {
+        self.taxonomic
+            .iter()
+            .find(|taxon| taxon.scientific_name == scientific_name)
+    }
+
+    /// License with the given scope (`data` or `media`).
+    pub fn license(&self, scope: &str) -> Option<&License> {
+        self.licenses
+            .iter()
+            .find(|license| license.scope.as_deref() == Some(scope))
+    }
+
+    /// Contributors with the given role.
+    pub fn contributors_with_role<'a>(
+        &'a self,
+        role: &'a str,
+    ) -> impl Iterator<Item = &'a Contributor> {
+        self.contributors
+            .iter()
+            .filter(move |contributor| contributor.role.as_deref() == Some(role))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::util::test::temp_path;
+
+    /// Metadata modelled on the Camtrap DP example dataset.
+    pub(crate) fn example() -> PackageMetadata {
+        serde_json::from_str(
+            r#"{
+                "name": "camtrap-dp-example-dataset",
+                "id": "7cca70f5-ef8c-4f86-85fb-8f070937d7ab",
+                "created": "2023-02-06T11:23:03Z",
+                "title": "Sample from: MICA - Muskrat and coypu camera trap observations in Belgium, the Netherlands and Germany",
+                "contributors": [
+                    {"title": "Axel Neukermans", "email": "axel.neukermans@inbo.be", "role": "contributor", "organization": "Research Institute for Nature and Forest (INBO)"},
+                    {"title": "Peter Desmet", "email": "peter.desmet@inbo.be", "path": "https://orcid.org/0000-0002-8442-8025", "role": "principalInvestigator", "organization": "Research Institute for Nature and Forest (INBO)"},
+                    {"title": "Research Institute for Nature and Forest (INBO)", "path": "https://inbo.be", "role": "rightsHolder"}
+                ],
+                "description": "MICA - Muskrat and coypu camera trap observations.",
+                "version": "1.0",
+                "keywords": ["camera traps", "invasive species"],
+                "licenses": [
+                    {"name": "CC0-1.0", "scope": "data"},
+                    {"path": "http://creativecommons.org/licenses/by/4.0/", "scope": "media"}
+                ],
+                "bibliographicCitation": "Desmet P (2023) Sample from: MICA.",
+                "project": {
+                    "id": "86cabc14-d475-4439-98a7-e7b590bed60e",
+                    "title": "Management of Invasive Coypu and muskrAt in Europe",
+                    "acronym": "MICA",
+                    "samplingDesign": "targeted",
+                    "captureMethod": ["activityDetection", "timeLapse"],
+                    "individualAnimals": false,
+                    "observationLevel": ["media", "event"]
+                },
+                "coordinatePrecision": 0.001,
+                "spatial": {"type": "Polygon", "coordinates": [[[4.013, 50.699], [5.659, 50.699], [5.659, 51.496], [4.013, 51.496], [4.013, 50.699]]]},
+                "temporal": {"start": "2020-05-30", "end": "2021-04-18"},
+                "taxonomic": [
+                    {"scientificName": "Anas platyrhynchos", "taxonID": "https://www.checklistbank.org/dataset/COL2023/taxon/DGP6", "taxonRank": "species", "vernacularNames": {"eng": "mallard", "nld": "wilde eend"}}
+                ],
+                "profile": "https://raw.githubusercontent.com/tdwg/camtrap-dp/1.0/camtrap-dp-profile.json"
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn metadata_round_trip() {
+        let metadata = example();
+        assert_eq!(
+            metadata.project.as_ref().unwrap().acronym.as_deref(),
+            Some("MICA")
+        );
+        assert_eq!(
+            metadata.license("data").unwrap().name.as_deref(),
+            Some("CC0-1.0")
+        );
+        assert!(metadata.other.contains_key("profile"));
+
+        let path = temp_path("datapackage.json");
+        metadata.to_file(&path).unwrap();
+        let metadata_out = PackageMetadata::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(metadata_out, metadata);
+    }
+
+    #[test]
+    fn nested_properties_round_trip() {
+        let descriptor = serde_json::json!({
+            "contributors": [
+                {"title": "Peter Desmet", "firstName": "Peter", "lastName": "Desmet", "role": "principalInvestigator"}
+            ],
+            "licenses": [{"name": "CC0-1.0", "scope": "data", "note": "waived"}],
+            "project": {"title": "MICA", "path": "https://lifemica.eu", "funder": "LIFE"},
+            "temporal": {"start": "2020-05-30", "end": "2021-04-18", "precision": "day"},
+            "taxonomic": [
+                {"scientificName": "Anas platyrhynchos", "order": "Anseriformes", "family": "Anatidae"}
+            ]
+        });
+        let metadata: PackageMetadata = serde_json::from_value(descriptor.clone()).unwrap();
+        assert_eq!(metadata.contributors[0].other["lastName"], "Desmet");
+        assert_eq!(metadata.taxonomic[0].other["family"], "Anatidae");
+        assert_eq!(serde_json::to_value(&metadata).unwrap(), descriptor);
+    }
+}
@@ -0,0 +1,258 @@
+//! Site × occasion detection histories for occupancy modelling.
+//!
+//! The histories are exported in the wide CSV layout used by `unmarked` and `camtrapR`: one
+//! row per site and one column per occasion (`o1`, `o2`, ...), with `NA` for occasions
+//! without effort.
+
+use crate::effort::EffortOptions;
+use crate::independence::{GroupBy, IndependenceOptions};
+use crate::Package;
+use chrono::{DateTime, Duration, FixedOffset, TimeZone};
+use std::collections::BTreeMap;
+
+/// Options for [`Package::detection_history`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OccupancyOptions {
+    /// Length of a sampling occasion.
+    pub occasion_length: Duration,
+
+    /// Site at which detections are grouped (deployment or location).
+    pub group_by: GroupBy,
+
+    /// Start of the first occasion. Defaults to midnight on the day the earliest deployment
+    /// started.
+    pub start: Option<DateTime<FixedOffset>>,
+
+    /// Options used to select detections (observation level and timestamp source).
+    pub independence: IndependenceOptions,
+
+    /// Options used to compute effort.
+    pub effort: EffortOptions,
+}
+
+impl Default for OccupancyOptions {
+    fn default() -> Self {
+        OccupancyOptions {
+            occasion_length: Duration::days(7),
+            group_by: GroupBy::Location,
+            start: None,
+            independence: IndependenceOptions::default(),
+            effort: EffortOptions::default(),
+        }
+    }
+}
+
+/// Detection history of a species, with the effort matrix to be used as an observation
+/// covariate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectionHistory {
+    /// Scientific name of the species.
+    pub scientific_name: String,
+
+    /// Sites (deploymentID or locationID), in row order.
+    pub sites: Vec<String>,
+
+    /// Start of each occasion, in column order.
+    pub occasions: Vec<DateTime<FixedOffset>>,
+
+    /// Detections per site and occasion: `Some(1)` if detected, `Some(0)` if not detected and
+    /// `None` if there was no effort.
+    pub detections: Vec<Vec<Option<u8>>>,
+
+    /// Active camera-days per site and occasion.
+    pub effort: Vec<Vec<f64>>,
+}
+
+impl DetectionHistory {
+    /// Write the detection matrix to a CSV file.
+    pub fn detections_to_file(&self, path: &str) -> Result<(), csv::Error> {
+        self.write_matrix(path, |row, column| {
+            self.detections[row][column].map(|detected| detected.to_string())
+        })
+    }
+
+    /// Write the effort matrix (camera-days) to a CSV file. Occasions without effort are
+    /// written as `NA`.
+    pub fn effort_to_file(&self, path: &str) -> Result<(), csv::Error> {
+        self.write_matrix(path, |row, column| {
+            let effort = self.effort[row][column];
+            (effort > 0.0).then(|| effort.to_string())
+        })
+    }
+
+    fn write_matrix(
+        &self,
+        path: &str,
+        cell: impl Fn(usize, usize) -> Option<String>,
+    ) -> Result<(), csv::Error> {
+        let mut wtr = csv::Writer::from_path(path)?;
+
+        let mut header = vec!["site".to_string()];
+        header.extend((1..=self.occasions.len()).map(|n| format!("o{}", n)));
+        wtr.write_record(&header)?;
+
+        for (row, site) in self.sites.iter().enumerate() {
+            let mut record = vec![site.clone()];
+            record.extend(
+                (0..self.occasions.len())
+                    .map(|column| cell(row, column).unwrap_or_else(|| "NA".to_string())),
+            );
+            wtr.write_record(&record)?;
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl Package {
+    /// Detection history of a species per site and occasion. Occasions run from the start
+    /// until the latest effective deployment end.
+    pub fn detection_history(
+        &self,
+        scientific_name: &str,
+        options: &OccupancyOptions,
+    ) -> DetectionHistory {
+        let site = |deployment_id: &str| -> String {
+            match (options.group_by, self.deployment(deployment_id)) {
+                (GroupBy::Location, Some(deployment)) => deployment
+                    .location_id
+                    .clone()
+                    .unwrap_or_else(|| deployment_id.to_string()),
+                _ => deployment_id.to_string(),
+            }
+        };
+
+        let mut intervals: BTreeMap<String, Vec<_>> = BTreeMap::new();
+        for deployment in &self.deployments {
+            intervals
+                .entry(site(&deployment.deployment_id))
+                .or_default()
+                .extend(self.active_intervals(deployment, &options.effort));
+        }
+
+        let start = options.start.unwrap_or_else(|| {
+            let first = self
+                .deployments
+                .iter()
+                .map(|d| d.deployment_start)
+                .min()
+                .unwrap_or_default();
+            first
+                .timezone()
+                .from_local_datetime(&first.date_naive().and_hms_opt(0, 0, 0).unwrap())
+                .unwrap()
+        });
+        let end = intervals
+            .values()
+            .flatten()
+            .map(|(_, end)| *end)
+            .max()
+            .unwrap_or(start);
+
+        let mut occasions = Vec::new();
+        let mut occasion = start;
+        while occasion < end && options.occasion_length > Duration::zero() {
+            occasions.push(occasion);
+            occasion += options.occasion_length;
+        }
+
+        let detections: Vec<(String, DateTime<FixedOffset>)> = self
+            .independent_detections(&IndependenceOptions {
+                threshold: Duration::zero(),
+                ..options.independence.clone()
+            })
+            .into_iter()
+            .filter(|d| d.scientific_name == scientific_name)
+            .map(|d| (site(&d.deployment_id), d.timestamp))
+            .collect();
+
+        let sites: Vec<String> = intervals.keys().cloned().collect();
+        let mut history = DetectionHistory {
+            scientific_name: scientific_name.to_string(),
+            sites: sites.clone(),
+            occasions: occasions.clone(),
+            detections: Vec::with_capacity(sites.len()),
+            effort: Vec::with_capacity(sites.len()),
+        };
+
+        for site in &sites {
+            let mut detection_row = Vec::with_capacity(occasions.len());
+            let mut effort_row = Vec::with_capacity(occasions.len());
+            for occasion_start in &occasions {
+                let occasion_end = *occasion_start + options.occasion_length;
+                let effort = intervals[site]
+                    .iter()
+                    .map(|(start, end)| {
+                        (*end.min(&occasion_end) - *start.max(occasion_start)).max(Duration::zero())
+                    })
+                    .fold(Duration::zero(), |total, duration| total + duration)
+                    .num_seconds() as f64
+                    / 86400.0;
+                let detected = detections.iter().any(|(s, timestamp)| {
+                    s == site && timestamp >= occasion_start && *timestamp < occasion_end
+                });
+
+                effort_row.push(effort);
+                detection_row.push((effort > 0.0).then_some(detected as u8));
+            }
+            history.detections.push(detection_row);
+            history.effort.push(effort_row);
+        }
+
+        history
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::test::temp_path;
+
+    #[test]
+    fn weekly_detection_history() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let history = package.detection_history("Anas platyrhynchos", &OccupancyOptions::default());
+
+        assert_eq!(history.sites.len(), 4);
+        assert_eq!(history.detections.len(), 4);
+        assert!(history
+            .detections
+            .iter()
+            .all(|row| row.len() == history.occasions.len()));
+
+        for (detections, effort) in history.detections.iter().zip(&history.effort) {
+            for (detection, effort) in detections.iter().zip(effort) {
+                assert_eq!(detection.is_none(), *effort == 0.0);
+            }
+        }
+        assert!(history.detections.iter().flatten().any(|d| *d == Some(1)));
+    }
+
+    #[test]
+    fn detection_history_to_file() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let history = package.detection_history(
+            "Anas platyrhynchos",
+            &OccupancyOptions {
+                group_by: GroupBy::Deployment,
+                ..Default::default()
+            },
+        );
+
+        let path = temp_path("detection_history.csv");
+        history.detections_to_file(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut lines = content.lines();
+        assert!(lines.next().unwrap().starts_with("site,o1,o2"));
+        assert!(content.contains("NA"));
+        assert_eq!(lines.count(), 4);
+
+        let path = temp_path("detection_history_effort.csv");
+        history.effort_to_file(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(content.starts_with("site,o1,o2"));
+    }
+}
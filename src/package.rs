@@ -0,0 +1,131 @@
+//! A Camera Trap Data Package as a whole: its metadata, deployments, media and observations.
+
+use crate::metadata::PackageMetadata;
+use crate::{Deployment, Medium, Observation, ReadDataPackageCsv, WriteDataPackageCsv};
+use std::path::Path;
+
+/// Camera Trap Data Package, holding the data of the deployments, media and observations
+/// resources.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Package {
+    /// Package metadata (`datapackage.json`), if available.
+    pub metadata: Option<PackageMetadata>,
+
+    /// Camera trap placements (deployments).
+    pub deployments: Vec<Deployment>,
+
+    /// Media files recorded during the deployments.
+    pub media: Vec<Medium>,
+
+    /// Observations derived from the media files.
+    pub observations: Vec<Observation>,
+}
+
+impl Package {
+    /// Create a package from its resources.
+    pub fn new(
+        deployments: Vec<Deployment>,
+        media: Vec<Medium>,
+        observations: Vec<Observation>,
+    ) -> Self {
+        Package {
+            metadata: None,
+            deployments,
+            media,
+            observations,
+        }
+    }
+
+    /// Read a package from a directory containing `deployments.csv`, `media.csv` and
+    /// `observations.csv`, and optionally `datapackage.json`.
+    pub fn from_dir(path: &str) -> Result<Self, PackageError> {
+        let dir = Path::new(path);
+        let descriptor = resource_path(dir, "datapackage.json");
+        Ok(Package {
+            metadata: if Path::new(&descriptor).exists() {
+                Some(PackageMetadata::from_file(&descriptor)?)
+            } else {
+                None
+            },
+            deployments: Deployment::from_file(&resource_path(dir, "deployments.csv"))
+                .map_err(PackageError::Csv)?,
+            media: Medium::from_file(&resource_path(dir, "media.csv"))
+                .map_err(PackageError::Csv)?,
+            observations: Observation::from_file(&resource_path(dir, "observations.csv"))
+                .map_err(PackageError::Csv)?,
+        })
+    }
+
+    /// Write the package to a directory as `deployments.csv`, `media.csv` and
+    /// `observations.csv`, and `datapackage.json` if it has metadata. The directory must
+    /// exist.
+    pub fn to_dir(&self, path: &str) -> Result<(), PackageError> {
+        let dir = Path::new(path);
+        if let Some(metadata) = &self.metadata {
+            metadata.to_file(&resource_path(dir, "datapackage.json"))?;
+        }
+        self.deployments
+            .to_file(&resource_path(dir, "deployments.csv"))
+            .map_err(PackageError::Csv)?;
+        self.media
+            .to_file(&resource_path(dir, "media.csv"))
+            .map_err(PackageError::Csv)?;
+        self.observations
+            .to_file(&resource_path(dir, "observations.csv"))
+            .map_err(PackageError::Csv)?;
+        Ok(())
+    }
+
+    /// Deployment with the given identifier.
+    pub fn deployment(&self, deployment_id: &str) -> Option<&Deployment> {
+        self.deployments
+            .iter()
+            .find(|deployment| deployment.deployment_id == deployment_id)
+    }
+
+    /// Media file with the given identifier.
+    pub fn medium(&self, media_id: &str) -> Option<&Medium> {
+        self.media.iter().find(|medium| medium.media_id == media_id)
+    }
+}
+
+/// Error type for reading and writing packages.
+#[derive(Debug)]
+pub enum PackageError {
+    Io(std::io::Error),
+    Csv(csv::Error),
+    Json(serde_json::Error),
+}
+
+pub(crate) fn resource_path(dir: &Path, name: &str) -> String {
+    dir.join(name).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::test::temp_path;
+
+    #[test]
+    fn package_from_dir() {
+        let package = Package::from_dir("fixtures").unwrap();
+        assert_eq!(package.deployments.len(), 4);
+        assert_eq!(package.media.len(), 423);
+        assert_eq!(package.observations.len(), 549);
+        assert!(package.deployment("00a2c20d").is_some());
+        assert!(package.medium("07840dcc").is_some());
+    }
+
+    #[test]
+    fn package_to_dir() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        assert_eq!(package.metadata, None);
+        package.metadata = Some(crate::metadata::test::example());
+        let dir = temp_path("package_to_dir");
+        std::fs::create_dir_all(&dir).unwrap();
+        package.to_dir(&dir).unwrap();
+        let package_out = Package::from_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(package, package_out);
+    }
+}
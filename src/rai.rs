@@ -0,0 +1,190 @@
+//! Relative abundance index (RAI): independent detections per 100 camera-days.
+
+use crate::effort::EffortOptions;
+use crate::independence::IndependenceOptions;
+use crate::{Deployment, Package, WriteDataPackageCsv};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Site at which detections and effort are summed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaiGroupBy {
+    /// Per deployment (deploymentID).
+    Deployment,
+
+    /// Per location (locationID), falling back to the deployment for deployments without a
+    /// location.
+    Location,
+
+    /// Per deployment group. A deployment contributes to each of its groups. If a key is
+    /// given, only groups with that key (e.g. `area`) are used.
+    DeploymentGroup(Option<String>),
+}
+
+/// Options for [`Package::rai`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RaiOptions {
+    /// Site at which detections and effort are summed.
+    pub group_by: RaiGroupBy,
+
+    /// If true, the number of individuals (`count`) of each detection is summed instead of
+    /// the number of detections.
+    pub sum_count: bool,
+
+    /// Options used to derive independent detections.
+    pub independence: IndependenceOptions,
+
+    /// Options used to compute effort.
+    pub effort: EffortOptions,
+}
+
+impl Default for RaiOptions {
+    fn default() -> Self {
+        RaiOptions {
+            group_by: RaiGroupBy::Deployment,
+            sum_count: false,
+            independence: IndependenceOptions::default(),
+            effort: EffortOptions::default(),
+        }
+    }
+}
+
+/// Relative abundance index of a species at a site.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Rai {
+    /// Site (deploymentID, locationID or deployment group, depending on the grouping).
+    pub site: String,
+
+    /// Scientific name of the species.
+    #[serde(rename = "scientificName")]
+    pub scientific_name: String,
+
+    /// Number of independent detections (or individuals, if counts are summed).
+    pub detections: u32,
+
+    /// Active camera-days at the site.
+    #[serde(rename = "cameraDays")]
+    pub camera_days: f64,
+
+    /// Detections per 100 camera-days. `None` for sites without effort.
+    pub rai: Option<f64>,
+}
+
+impl WriteDataPackageCsv<Rai> for Vec<Rai> {}
+
+impl RaiGroupBy {
+    /// Sites a deployment belongs to.
+    pub(crate) fn sites(&self, deployment: &Deployment) -> Vec<String> {
+        match self {
+            RaiGroupBy::Deployment => vec![deployment.deployment_id.clone()],
+            RaiGroupBy::Location => vec![deployment
+                .location_id
+                .clone()
+                .unwrap_or_else(|| deployment.deployment_id.clone())],
+            RaiGroupBy::DeploymentGroup(key) => deployment
+                .parsed_deployment_groups()
+                .into_iter()
+                .filter(|group| key.is_none() || group.key == *key)
+                .map(|group| group.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Package {
+    /// Relative abundance index per species and site, as a tidy table ordered by site and
+    /// species. Every species detected in the package is listed for every site with effort,
+    /// so sites where a species was not detected have zero detections.
+    pub fn rai(&self, options: &RaiOptions) -> Vec<Rai> {
+        let mut camera_days: BTreeMap<String, f64> = BTreeMap::new();
+        for effort in self.effort(&options.effort) {
+            let deployment = match self.deployment(&effort.deployment_id) {
+                Some(deployment) => deployment,
+                None => continue,
+            };
+            for site in options.group_by.sites(deployment) {
+                *camera_days.entry(site).or_insert(0.0) += effort.camera_days;
+            }
+        }
+
+        let mut species: BTreeSet<String> = BTreeSet::new();
+        let mut detections: BTreeMap<(String, String), u32> = BTreeMap::new();
+        for detection in self.independent_detections(&options.independence) {
+            let deployment = match self.deployment(&detection.deployment_id) {
+                Some(deployment) => deployment,
+                None => continue,
+            };
+            let n = if options.sum_count {
+                detection.count.unwrap_or(1)
+            } else {
+                1
+            };
+            for site in options.group_by.sites(deployment) {
+                *detections
+                    .entry((site, detection.scientific_name.clone()))
+                    .or_insert(0) += n;
+            }
+            species.insert(detection.scientific_name);
+        }
+
+        let mut table = Vec::new();
+        for (site, camera_days) in camera_days {
+            for scientific_name in &species {
+                let n = detections
+                    .get(&(site.clone(), scientific_name.clone()))
+                    .copied()
+                    .unwrap_or(0);
+                table.push(Rai {
+                    site: site.clone(),
+                    scientific_name: scientific_name.clone(),
+                    detections: n,
+                    camera_days,
+                    rai: (camera_days > 0.0).then(|| n as f64 / camera_days * 100.0),
+                });
+            }
+        }
+
+        table
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rai_per_deployment() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let table = package.rai(&RaiOptions::default());
+
+        let detections = package
+            .independent_detections(&IndependenceOptions::default())
+            .len() as u32;
+        assert_eq!(table.iter().map(|r| r.detections).sum::<u32>(), detections);
+
+        let mallard = table
+            .iter()
+            .find(|r| r.site == "00a2c20d" && r.scientific_name == "Anas platyrhynchos")
+            .unwrap();
+        // 9 mallard events with 6 distinct starts at least 30 minutes apart, over
+        // 2020-05-30T04:57:37 to 2020-07-01T11:41:41 (32 days, 6:44:04).
+        let camera_days = 2_789_044.0 / 86400.0;
+        assert_eq!(mallard.detections, 6);
+        assert!((mallard.camera_days - camera_days).abs() < 1e-9);
+        assert!((mallard.rai.unwrap() - 600.0 / camera_days).abs() < 1e-9);
+        assert!((mallard.rai.unwrap() - 18.587).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rai_per_deployment_group() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let table = package.rai(&RaiOptions {
+            group_by: RaiGroupBy::DeploymentGroup(Some("season".to_string())),
+            sum_count: true,
+            ..Default::default()
+        });
+
+        let sites: BTreeSet<_> = table.iter().map(|r| r.site.as_str()).collect();
+        assert_eq!(sites, BTreeSet::from(["season:spring", "season:summer"]));
+    }
+}
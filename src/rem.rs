@@ -0,0 +1,357 @@
+//! Random encounter model (REM) density estimation (Rowcliffe et al. 2008).
+//!
+//! Density is estimated as `D = y / t · π / (v · r · (2 + θ))`, with `y / t` the trapping
+//! rate, `v` the day range, `r` the detection zone radius and `θ` the detection zone angle.
+//! All parameters are derived from the package: radius and angle from
+//! `individualPositionRadius` and `individualPositionAngle`, speed from `individualSpeed` and
+//! activity level from the diel activity pattern (Rowcliffe et al. 2014).
+
+use crate::activity::{clock_time, ActivityPattern};
+use crate::effort::EffortOptions;
+use crate::independence::IndependenceOptions;
+use crate::{ObservationType, Package};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+/// Options for [`Package::rem`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemOptions {
+    /// Options used to derive the independent detections (the encounters).
+    pub independence: IndependenceOptions,
+
+    /// Options used to compute effort.
+    pub effort: EffortOptions,
+
+    /// Number of bootstrap replicates used for the confidence interval. No interval is
+    /// computed if zero.
+    pub bootstrap: usize,
+
+    /// Confidence level of the interval (e.g. 0.95).
+    pub confidence: f64,
+
+    /// Seed of the random number generator used for bootstrapping.
+    pub seed: u64,
+}
+
+impl Default for RemOptions {
+    fn default() -> Self {
+        RemOptions {
+            independence: IndependenceOptions::default(),
+            effort: EffortOptions::default(),
+            bootstrap: 1000,
+            confidence: 0.95,
+            seed: 0,
+        }
+    }
+}
+
+/// Random encounter model estimate for a species.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemEstimate {
+    /// Scientific name of the species.
+    pub scientific_name: String,
+
+    /// Number of independent detections.
+    pub detections: usize,
+
+    /// Active camera-days.
+    pub camera_days: f64,
+
+    /// Effective detection radius, in meters.
+    pub radius: Option<f64>,
+
+    /// Effective detection angle, in radians.
+    pub angle: Option<f64>,
+
+    /// Average speed while active (harmonic mean), in meters per second.
+    pub speed: Option<f64>,
+
+    /// Proportion of the day the species is active.
+    pub activity_level: Option<f64>,
+
+    /// Day range (distance travelled per day), in kilometers per day.
+    pub day_range: Option<f64>,
+
+    /// Density, in individuals per square kilometer.
+    pub density: Option<f64>,
+
+    /// Lower bound of the bootstrap confidence interval of the density.
+    pub density_lower: Option<f64>,
+
+    /// Upper bound of the bootstrap confidence interval of the density.
+    pub density_upper: Option<f64>,
+}
+
+/// Data of a species from which the parameters are estimated.
+#[derive(Debug, Clone, Default)]
+struct SpeciesData {
+    /// Detections and camera-days per deployment.
+    deployments: Vec<(usize, f64)>,
+    radii: Vec<f64>,
+    angles: Vec<f64>,
+    speeds: Vec<f64>,
+    times: Vec<f64>,
+}
+
+impl SpeciesData {
+    fn resample(&self, rng: &mut StdRng) -> SpeciesData {
+        SpeciesData {
+            deployments: resample(&self.deployments, rng),
+            radii: resample(&self.radii, rng),
+            angles: resample(&self.angles, rng),
+            speeds: resample(&self.speeds, rng),
+            times: resample(&self.times, rng),
+        }
+    }
+}
+
+fn resample<T: Clone>(values: &[T], rng: &mut StdRng) -> Vec<T> {
+    (0..values.len())
+        .map(|_| values[rng.random_range(0..values.len())].clone())
+        .collect()
+}
+
+/// Effective detection radius of a half-normal point transect detection function,
+/// `σ √2` with `σ² = Σ r² / 2n`.
+fn radius(radii: &[f64]) -> Option<f64> {
+    if radii.is_empty() {
+        return None;
+    }
+    let sigma = (radii.iter().map(|r| r * r).sum::<f64>() / (2.0 * radii.len() as f64)).sqrt();
+    Some(sigma * 2f64.sqrt())
+}
+
+/// Effective detection angle (full width, in radians) of a half-normal detection function
+/// fitted to the angles from the centerline, `2 σ √(π/2)` with `σ² = Σ θ² / n`.
+fn angle(angles: &[f64]) -> Option<f64> {
+    if angles.is_empty() {
+        return None;
+    }
+    let sigma = (angles.iter().map(|a| a * a).sum::<f64>() / angles.len() as f64).sqrt();
+    Some(2.0 * sigma * (PI / 2.0).sqrt())
+}
+
+/// Harmonic mean of the speeds.
+fn speed(speeds: &[f64]) -> Option<f64> {
+    let speeds: Vec<f64> = speeds.iter().copied().filter(|s| *s > 0.0).collect();
+    if speeds.is_empty() {
+        return None;
+    }
+    Some(speeds.len() as f64 / speeds.iter().map(|s| 1.0 / s).sum::<f64>())
+}
+
+/// Activity level as `1 / (2π max f)`, with `f` the kernel density of the detection times.
+fn activity_level(times: &[f64]) -> Option<f64> {
+    if times.len() < 2 {
+        return None;
+    }
+    let pattern = ActivityPattern {
+        scientific_name: String::new(),
+        times: times.to_vec(),
+    };
+    let grid: Vec<f64> = (0..512).map(|i| 2.0 * PI * i as f64 / 512.0).collect();
+    let max = pattern.density(&grid, 1.0).into_iter().fold(0.0, f64::max);
+    Some((1.0 / (2.0 * PI * max)).min(1.0))
+}
+
+/// Parameters and density estimated from the data of a species.
+struct Parameters {
+    radius: Option<f64>,
+    angle: Option<f64>,
+    speed: Option<f64>,
+    activity_level: Option<f64>,
+    day_range: Option<f64>,
+    density: Option<f64>,
+}
+
+fn estimate(data: &SpeciesData) -> Parameters {
+    let radius = radius(&data.radii);
+    let angle = angle(&data.angles);
+    let speed = speed(&data.speeds);
+    let activity_level = activity_level(&data.times);
+    let day_range = speed
+        .zip(activity_level)
+        .map(|(speed, activity)| speed * activity * 86400.0);
+
+    let detections: usize = data.deployments.iter().map(|(y, _)| y).sum();
+    let camera_days: f64 = data.deployments.iter().map(|(_, t)| t).sum();
+    let density = match (day_range, radius, angle) {
+        (Some(v), Some(r), Some(theta)) if camera_days > 0.0 && v > 0.0 && r > 0.0 => {
+            let per_square_meter = detections as f64 / camera_days * PI / (v * r * (2.0 + theta));
+            Some(per_square_meter * 1e6)
+        }
+        _ => None,
+    };
+
+    Parameters {
+        radius,
+        angle,
+        speed,
+        activity_level,
+        day_range: day_range.map(|v| v / 1000.0),
+        density,
+    }
+}
+
+impl Package {
+    /// Random encounter model density estimates per species, ordered by scientific name.
+    pub fn rem(&self, options: &RemOptions) -> Vec<RemEstimate> {
+        let camera_days: Vec<(String, f64)> = self
+            .effort(&options.effort)
+            .into_iter()
+            .map(|effort| (effort.deployment_id, effort.camera_days))
+            .collect();
+
+        let mut species: BTreeMap<String, SpeciesData> = BTreeMap::new();
+        let detections = self.independent_detections(&options.independence);
+        for detection in &detections {
+            let data = species
+                .entry(detection.scientific_name.clone())
+                .or_default();
+            if let Some(deployment) = self.deployment(&detection.deployment_id) {
+                data.times.push(clock_time(
+                    &detection
+                        .timestamp
+                        .with_timezone(&deployment.deployment_start.timezone()),
+                ));
+            }
+        }
+        for (scientific_name, data) in species.iter_mut() {
+            data.deployments = camera_days
+                .iter()
+                .map(|(deployment_id, t)| {
+                    let y = detections
+                        .iter()
+                        .filter(|d| {
+                            &d.deployment_id == deployment_id
+                                && &d.scientific_name == scientific_name
+                        })
+                        .count();
+                    (y, *t)
+                })
+                .collect();
+        }
+
+        for observation in self
+            .observations
+            .iter()
+            .filter(|o| o.observation_type == ObservationType::Animal)
+        {
+            let data = match observation
+                .scientific_name
+                .as_ref()
+                .and_then(|name| species.get_mut(name))
+            {
+                Some(data) => data,
+                None => continue,
+            };
+            data.radii.extend(observation.individual_position_radius);
+            data.angles.extend(
+                observation
+                    .individual_position_angle
+                    .map(|angle| angle.to_radians()),
+            );
+            data.speeds.extend(observation.individual_speed);
+        }
+
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        species
+            .into_iter()
+            .map(|(scientific_name, data)| {
+                let parameters = estimate(&data);
+                let (density_lower, density_upper) = match parameters.density {
+                    Some(_) if options.bootstrap > 0 => {
+                        let mut replicates: Vec<f64> = (0..options.bootstrap)
+                            .filter_map(|_| estimate(&data.resample(&mut rng)).density)
+                            .collect();
+                        replicates.sort_by(|a, b| a.total_cmp(b));
+                        let alpha = (1.0 - options.confidence) / 2.0;
+                        (
+                            quantile(&replicates, alpha),
+                            quantile(&replicates, 1.0 - alpha),
+                        )
+                    }
+                    _ => (None, None),
+                };
+
+                RemEstimate {
+                    scientific_name,
+                    detections: data.deployments.iter().map(|(y, _)| y).sum(),
+                    camera_days: data.deployments.iter().map(|(_, t)| t).sum(),
+                    radius: parameters.radius,
+                    angle: parameters.angle,
+                    speed: parameters.speed,
+                    activity_level: parameters.activity_level,
+                    day_range: parameters.day_range,
+                    density: parameters.density,
+                    density_lower,
+                    density_upper,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Quantile of sorted values, using linear interpolation.
+fn quantile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let position = p * (sorted.len() - 1) as f64;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    let fraction = position - lower as f64;
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * fraction)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parameter_estimators() {
+        assert!((radius(&[1.0, 1.0]).unwrap() - 1.0).abs() < 1e-12);
+        assert!((speed(&[1.0, 0.5]).unwrap() - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(angle(&[]), None);
+        assert!(angle(&[0.2, -0.3]).unwrap() > 0.0);
+
+        let uniform: Vec<f64> = (0..200).map(|i| 2.0 * PI * i as f64 / 200.0).collect();
+        assert!(activity_level(&uniform).unwrap() > 0.9);
+    }
+
+    #[test]
+    fn density_with_bootstrap_interval() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        for (i, observation) in package
+            .observations
+            .iter_mut()
+            .filter(|o| o.scientific_name.as_deref() == Some("Anas platyrhynchos"))
+            .enumerate()
+        {
+            observation.individual_position_radius = Some(1.0 + (i % 5) as f64);
+            observation.individual_position_angle = Some(-20.0 + (i % 9) as f64 * 5.0);
+            observation.individual_speed = Some(0.2 + (i % 3) as f64 * 0.1);
+        }
+
+        let options = RemOptions {
+            bootstrap: 200,
+            ..Default::default()
+        };
+        let estimates = package.rem(&options);
+        let mallard = estimates
+            .iter()
+            .find(|e| e.scientific_name == "Anas platyrhynchos")
+            .unwrap();
+        let density = mallard.density.unwrap();
+        assert!(density > 0.0);
+        assert!(mallard.density_lower.unwrap() <= mallard.density_upper.unwrap());
+        assert_eq!(package.rem(&options)[0], estimates[0]);
+
+        let other = estimates
+            .iter()
+            .find(|e| e.scientific_name != "Anas platyrhynchos")
+            .unwrap();
+        assert_eq!(other.density, None);
+    }
+}
@@ -0,0 +1,403 @@
+//! SQLite database of a package: a table per resource, with the primary keys and foreign keys
+//! of the Camtrap DP relationships, indexes on the columns used to join and filter them
+//! (e.g. `observationType` and `scientificName`), and a `metadata` table holding
+//! `datapackage.json`. Requires the `sqlite` feature.
+//!
+//! Columns are named as in the CSV files. Timestamps are stored as ISO 8601 text with their
+//! UTC offset, booleans as 0/1 and `exifData` as JSON text.
+
+use crate::metadata::PackageMetadata;
+use crate::Package;
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params, Connection, OpenFlags};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Error type for reading and writing SQLite databases.
+#[derive(Debug)]
+pub enum SqliteError {
+    Io(std::io::Error),
+    Sqlite(rusqlite::Error),
+    Json(serde_json::Error),
+}
+
+/// Storage of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Text,
+    Real,
+    Integer,
+    Boolean,
+    Json,
+}
+
+/// Column of a table: name, storage and constraints.
+type Column = (&'static str, Kind, &'static str);
+
+const DEPLOYMENTS: &[Column] = &[
+    ("deploymentID", Kind::Text, "PRIMARY KEY NOT NULL"),
+    ("locationID", Kind::Text, ""),
+    ("locationName", Kind::Text, ""),
+    ("latitude", Kind::Real, ""),
+    ("longitude", Kind::Real, ""),
+    ("locationRadius", Kind::Real, ""),
+    ("deploymentStart", Kind::Text, "NOT NULL"),
+    ("deploymentEnd", Kind::Text, "NOT NULL"),
+    ("setupBy", Kind::Text, ""),
+    ("cameraID", Kind::Text, ""),
+    ("cameraModel", Kind::Text, ""),
+    ("cameraDelay", Kind::Real, ""),
+    ("cameraHeight", Kind::Real, ""),
+    ("cameraDepth", Kind::Real, ""),
+    ("cameraAngle", Kind::Real, ""),
+    ("cameraHeading", Kind::Real, ""),
+    ("detectionDistance", Kind::Real, ""),
+    ("timestampIssues", Kind::Boolean, ""),
+    ("baitUse", Kind::Boolean, ""),
+    ("featureType", Kind::Text, ""),
+    ("habitat", Kind::Text, ""),
+    ("deploymentGroups", Kind::Text, ""),
+    ("tags", Kind::Text, ""),
+    ("comments", Kind::Text, ""),
+];
+
+const MEDIA: &[Column] = &[
+    ("mediaID", Kind::Text, "PRIMARY KEY NOT NULL"),
+    (
+        "deploymentID",
+        Kind::Text,
+        "NOT NULL REFERENCES deployments(deploymentID)",
+    ),
+    ("captureMethod", Kind::Text, ""),
+    ("timestamp", Kind::Text, "NOT NULL"),
+    ("filePath", Kind::Text, "NOT NULL"),
+    ("filePublic", Kind::Boolean, "NOT NULL"),
+    ("fileName", Kind::Text, ""),
+    ("fileMediatype", Kind::Text, "NOT NULL"),
+    ("exifData", Kind::Json, ""),
+    ("favorite", Kind::Boolean, ""),
+    ("comments", Kind::Text, ""),
+];
+
+const OBSERVATIONS: &[Column] = &[
+    ("observationID", Kind::Text, "PRIMARY KEY NOT NULL"),
+    (
+        "deploymentID",
+        Kind::Text,
+        "NOT NULL REFERENCES deployments(deploymentID)",
+    ),
+    ("mediaID", Kind::Text, "REFERENCES media(mediaID)"),
+    ("eventID", Kind::Text, ""),
+    ("eventStart", Kind::Text, ""),
+    ("eventEnd", Kind::Text, ""),
+    ("observationLevel", Kind::Text, "NOT NULL"),
+    ("observationType", Kind::Text, "NOT NULL"),
+    ("cameraSetupType", Kind::Text, ""),
+    ("scientificName", Kind::Text, ""),
+    ("count", Kind::Integer, ""),
+    ("lifeStage", Kind::Text, ""),
+    ("sex", Kind::Text, ""),
+    ("behavior", Kind::Text, ""),
+    ("individualID", Kind::Text, ""),
+    ("individualPositionRadius", Kind::Real, ""),
+    ("individualPositionAngle", Kind::Real, ""),
+    ("individualSpeed", Kind::Real, ""),
+    ("bboxX", Kind::Real, ""),
+    ("bboxY", Kind::Real, ""),
+    ("bboxWidth", Kind::Real, ""),
+    ("bboxHeight", Kind::Real, ""),
+    ("classificationMethod", Kind::Text, ""),
+    ("classifiedBy", Kind::Text, ""),
+    ("classificationTimestamp", Kind::Text, ""),
+    ("classificationProbability", Kind::Real, ""),
+    ("observationTags", Kind::Text, ""),
+    ("observationComments", Kind::Text, ""),
+];
+
+const INDEXES: &[&str] = &[
+    "CREATE INDEX media_deploymentID ON media(deploymentID)",
+    "CREATE INDEX media_timestamp ON media(timestamp)",
+    "CREATE INDEX observations_deploymentID ON observations(deploymentID)",
+    "CREATE INDEX observations_mediaID ON observations(mediaID)",
+    "CREATE INDEX observations_eventID ON observations(eventID)",
+    "CREATE INDEX observations_observationType ON observations(observationType)",
+    "CREATE INDEX observations_scientificName ON observations(scientificName)",
+];
+
+/// Key of `datapackage.json` in the `metadata` table.
+const DESCRIPTOR: &str = "datapackage.json";
+
+fn create_table(connection: &Connection, table: &str, columns: &[Column]) -> rusqlite::Result<()> {
+    let definitions: Vec<String> = columns
+        .iter()
+        .map(|(name, kind, constraint)| {
+            let sql_type = match kind {
+                Kind::Text | Kind::Json => "TEXT",
+                Kind::Real => "REAL",
+                Kind::Integer | Kind::Boolean => "INTEGER",
+            };
+            format!("\"{}\" {} {}", name, sql_type, constraint)
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+    connection.execute(
+        &format!("CREATE TABLE {} ({})", table, definitions.join(", ")),
+        [],
+    )?;
+    Ok(())
+}
+
+fn insert<T: Serialize>(
+    connection: &Connection,
+    table: &str,
+    columns: &[Column],
+    rows: &[T],
+) -> Result<(), SqliteError> {
+    let names: Vec<String> = columns
+        .iter()
+        .map(|(name, _, _)| format!("\"{}\"", name))
+        .collect();
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    let mut statement = connection
+        .prepare(&format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table,
+            names.join(", "),
+            placeholders.join(", ")
+        ))
+        .map_err(SqliteError::Sqlite)?;
+
+    for row in rows {
+        let record = serde_json::to_value(row).map_err(SqliteError::Json)?;
+        let values = columns
+            .iter()
+            .map(|(name, kind, _)| to_sql(record.get(*name), *kind))
+            .collect::<Result<Vec<Value>, SqliteError>>()?;
+        statement
+            .execute(rusqlite::params_from_iter(values))
+            .map_err(SqliteError::Sqlite)?;
+    }
+    Ok(())
+}
+
+fn to_sql(value: Option<&serde_json::Value>, kind: Kind) -> Result<Value, SqliteError> {
+    Ok(match value {
+        None | Some(serde_json::Value::Null) => Value::Null,
+        Some(value) if kind == Kind::Json => {
+            Value::Text(serde_json::to_string(value).map_err(SqliteError::Json)?)
+        }
+        Some(serde_json::Value::Bool(b)) => Value::Integer(*b as i64),
+        Some(serde_json::Value::Number(n)) => match n.as_i64() {
+            Some(i) if kind == Kind::Integer => Value::Integer(i),
+            _ => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        Some(serde_json::Value::String(s)) => Value::Text(s.clone()),
+        Some(value) => Value::Text(value.to_string()),
+    })
+}
+
+fn select<T: DeserializeOwned>(
+    connection: &Connection,
+    table: &str,
+    columns: &[Column],
+) -> Result<Vec<T>, SqliteError> {
+    let names: Vec<String> = columns
+        .iter()
+        .map(|(name, _, _)| format!("\"{}\"", name))
+        .collect();
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT {} FROM {} ORDER BY rowid",
+            names.join(", "),
+            table
+        ))
+        .map_err(SqliteError::Sqlite)?;
+    let mut rows = statement.query([]).map_err(SqliteError::Sqlite)?;
+
+    let mut records = Vec::new();
+    while let Some(row) = rows.next().map_err(SqliteError::Sqlite)? {
+        let mut record = serde_json::Map::new();
+        for (i, (name, kind, _)) in columns.iter().enumerate() {
+            let value = row.get_ref(i).map_err(SqliteError::Sqlite)?;
+            record.insert(name.to_string(), from_sql(value, *kind)?);
+        }
+        records.push(
+            serde_json::from_value(serde_json::Value::Object(record)).map_err(SqliteError::Json)?,
+        );
+    }
+    Ok(records)
+}
+
+fn from_sql(value: ValueRef, kind: Kind) -> Result<serde_json::Value, SqliteError> {
+    Ok(match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) if kind == Kind::Boolean => serde_json::Value::Bool(i != 0),
+        ValueRef::Integer(i) if kind == Kind::Real => serde_json::Value::from(i as f64),
+        ValueRef::Integer(i) => serde_json::Value::from(i),
+        ValueRef::Real(f) => serde_json::Value::from(f),
+        ValueRef::Text(text) | ValueRef::Blob(text) => {
+            let text = String::from_utf8_lossy(text);
+            if kind == Kind::Json {
+                serde_json::from_str(&text).map_err(SqliteError::Json)?
+            } else {
+                serde_json::Value::String(text.into_owned())
+            }
+        }
+    })
+}
+
+impl Package {
+    /// Write the package to a new SQLite database. Fails if `path` already exists. Rows are
+    /// written in a single transaction with foreign keys enforced, so a package with dangling
+    /// references (e.g. an observation of an unknown media file) is not written and the file
+    /// is removed.
+    pub fn to_sqlite(&self, path: &str) -> Result<(), SqliteError> {
+        std::fs::File::create_new(path).map_err(SqliteError::Io)?;
+        let result = Connection::open(path)
+            .map_err(SqliteError::Sqlite)
+            .and_then(|connection| self.write_sqlite(connection));
+        if result.is_err() {
+            let _ = std::fs::remove_file(path);
+        }
+        result
+    }
+
+    fn write_sqlite(&self, mut connection: Connection) -> Result<(), SqliteError> {
+        connection
+            .pragma_update(None, "foreign_keys", true)
+            .map_err(SqliteError::Sqlite)?;
+        let transaction = connection.transaction().map_err(SqliteError::Sqlite)?;
+
+        transaction
+            .execute(
+                "CREATE TABLE metadata (key TEXT PRIMARY KEY NOT NULL, value TEXT NOT NULL)",
+                [],
+            )
+            .map_err(SqliteError::Sqlite)?;
+        create_table(&transaction, "deployments", DEPLOYMENTS).map_err(SqliteError::Sqlite)?;
+        create_table(&transaction, "media", MEDIA).map_err(SqliteError::Sqlite)?;
+        create_table(&transaction, "observations", OBSERVATIONS).map_err(SqliteError::Sqlite)?;
+        for index in INDEXES {
+            transaction
+                .execute(index, [])
+                .map_err(SqliteError::Sqlite)?;
+        }
+
+        if let Some(metadata) = &self.metadata {
+            let descriptor = serde_json::to_string_pretty(metadata).map_err(SqliteError::Json)?;
+            transaction
+                .execute(
+                    "INSERT INTO metadata (key, value) VALUES (?1, ?2)",
+                    params![DESCRIPTOR, descriptor],
+                )
+                .map_err(SqliteError::Sqlite)?;
+        }
+        insert(&transaction, "deployments", DEPLOYMENTS, &self.deployments)?;
+        insert(&transaction, "media", MEDIA, &self.media)?;
+        insert(
+            &transaction,
+            "observations",
+            OBSERVATIONS,
+            &self.observations,
+        )?;
+
+        transaction.commit().map_err(SqliteError::Sqlite)
+    }
+
+    /// Read a package from a SQLite database written by [`Package::to_sqlite`].
+    pub fn from_sqlite(path: &str) -> Result<Self, SqliteError> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(SqliteError::Sqlite)?;
+
+        let descriptor: Option<String> = connection
+            .query_row(
+                "SELECT value FROM metadata WHERE key = ?1",
+                params![DESCRIPTOR],
+                |row| row.get(0),
+            )
+            .map(Some)
+            .or_else(|error| match error {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                error => Err(SqliteError::Sqlite(error)),
+            })?;
+        let metadata: Option<PackageMetadata> = descriptor
+            .map(|descriptor| serde_json::from_str(&descriptor))
+            .transpose()
+            .map_err(SqliteError::Json)?;
+
+        Ok(Package {
+            metadata,
+            deployments: select(&connection, "deployments", DEPLOYMENTS)?,
+            media: select(&connection, "media", MEDIA)?,
+            observations: select(&connection, "observations", OBSERVATIONS)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn database(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("{}_{}.sqlite", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn sqlite_round_trip() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        package.metadata = Some(crate::metadata::test::example());
+        package.media[0].exif_data = Some(serde_json::json!({"Make": "RECONYX", "ISO": 400}));
+
+        let path = database("camtrap_dp_package");
+        package.to_sqlite(&path).unwrap();
+        let package_out = Package::from_sqlite(&path).unwrap();
+        assert_eq!(package_out, package);
+        assert_eq!(
+            package_out.media[0].timestamp.to_rfc3339(),
+            package.media[0].timestamp.to_rfc3339()
+        );
+
+        let connection = Connection::open(&path).unwrap();
+        let foreign_keys: i64 = connection
+            .query_row(
+                "SELECT count(*) FROM pragma_foreign_key_list('observations')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(foreign_keys, 2);
+        let indexes: i64 = connection
+            .query_row(
+                "SELECT count(*) FROM sqlite_master WHERE type = 'index' AND sql IS NOT NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexes as usize, INDEXES.len());
+        drop(connection);
+
+        match package.to_sqlite(&path) {
+            Err(SqliteError::Io(error)) => {
+                assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists)
+            }
+            result => panic!("expected an existing file error, got {:?}", result),
+        }
+        assert_eq!(Package::from_sqlite(&path).unwrap(), package);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn sqlite_dangling_reference() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        package.observations[0].media_id = Some("unknown".to_string());
+        let path = database("camtrap_dp_dangling");
+        assert!(matches!(
+            package.to_sqlite(&path),
+            Err(SqliteError::Sqlite(_))
+        ));
+        assert!(!std::path::Path::new(&path).exists());
+    }
+}
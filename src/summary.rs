@@ -0,0 +1,366 @@
+//! Summary statistics of a package, as an instant overview of its contents.
+
+use crate::effort::EffortOptions;
+use crate::util::name;
+use crate::{CaptureMethod, ClassificationMethod, ObservationLevel, ObservationType, Package};
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Number of species listed in [`Summary::top_species`].
+const TOP_SPECIES: usize = 10;
+
+/// Number of observations and individuals of a species.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SpeciesCount {
+    /// Scientific name of the species.
+    #[serde(rename = "scientificName")]
+    pub scientific_name: String,
+
+    /// Number of observations.
+    pub observations: usize,
+
+    /// Number of individuals (sum of `count`).
+    pub individuals: u32,
+}
+
+/// Overview of the contents of a package.
+///
+/// Statistics on observations use the event-level observations, or the media-level
+/// observations for packages without event-level observations.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Summary {
+    /// Number of deployments.
+    pub deployments: usize,
+
+    /// Earliest deployment start.
+    pub start: Option<DateTime<FixedOffset>>,
+
+    /// Latest deployment end.
+    pub end: Option<DateTime<FixedOffset>>,
+
+    /// Active camera-days of all deployments.
+    #[serde(rename = "cameraDays")]
+    pub camera_days: f64,
+
+    /// Number of media files.
+    pub media: usize,
+
+    /// Number of media files per capture method (`unknown` if not provided).
+    #[serde(rename = "mediaPerCaptureMethod")]
+    pub media_per_capture_method: BTreeMap<String, usize>,
+
+    /// Level of the observations the observation statistics are based on.
+    #[serde(rename = "observationLevel")]
+    pub observation_level: ObservationLevel,
+
+    /// Number of observations.
+    pub observations: usize,
+
+    /// Number of observations per observation type.
+    #[serde(rename = "observationsPerType")]
+    pub observations_per_type: BTreeMap<String, usize>,
+
+    /// Most observed species, by number of observations.
+    #[serde(rename = "topSpecies")]
+    pub top_species: Vec<SpeciesCount>,
+
+    /// Percentage of observations classified by humans.
+    #[serde(rename = "humanClassifiedPercentage")]
+    pub human_classified_percentage: f64,
+
+    /// Percentage of observations classified by machines.
+    #[serde(rename = "machineClassifiedPercentage")]
+    pub machine_classified_percentage: f64,
+
+    /// Percentage of observations that are blank.
+    #[serde(rename = "blankPercentage")]
+    pub blank_percentage: f64,
+
+    /// Identifiers of the deployments with `timestampIssues = true`.
+    #[serde(rename = "deploymentsWithTimestampIssues")]
+    pub deployments_with_timestamp_issues: Vec<String>,
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64 * 100.0
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Package {
+    /// Summary statistics of the package.
+    pub fn summary(&self) -> Summary {
+        let observation_level = if self
+            .observations
+            .iter()
+            .any(|o| o.observation_level == ObservationLevel::Event)
+        {
+            ObservationLevel::Event
+        } else {
+            ObservationLevel::Media
+        };
+        let observations: Vec<_> = self
+            .observations
+            .iter()
+            .filter(|o| o.observation_level == observation_level)
+            .collect();
+
+        let mut media_per_capture_method = BTreeMap::new();
+        for medium in &self.media {
+            let method = match &medium.capture_method {
+                Some(method) => name(method),
+                None => "unknown".to_string(),
+            };
+            *media_per_capture_method.entry(method).or_insert(0) += 1;
+        }
+        // List every capture method, so the overview also shows what is missing.
+        for method in [CaptureMethod::ActivityDetection, CaptureMethod::TimeLapse] {
+            media_per_capture_method.entry(name(&method)).or_insert(0);
+        }
+
+        let mut observations_per_type = BTreeMap::new();
+        let mut species: BTreeMap<&str, (usize, u32)> = BTreeMap::new();
+        for observation in &observations {
+            *observations_per_type
+                .entry(name(&observation.observation_type))
+                .or_insert(0) += 1;
+            if let (ObservationType::Animal, Some(scientific_name)) =
+                (&observation.observation_type, &observation.scientific_name)
+            {
+                let entry = species.entry(scientific_name).or_insert((0, 0));
+                entry.0 += 1;
+                entry.1 += observation.count.unwrap_or(0);
+            }
+        }
+        let mut top_species: Vec<SpeciesCount> = species
+            .into_iter()
+            .map(
+                |(scientific_name, (observations, individuals))| SpeciesCount {
+                    scientific_name: scientific_name.to_string(),
+                    observations,
+                    individuals,
+                },
+            )
+            .collect();
+        top_species.sort_by_key(|s| std::cmp::Reverse(s.observations));
+        top_species.truncate(TOP_SPECIES);
+
+        let classified = |method: ClassificationMethod| {
+            observations
+                .iter()
+                .filter(|o| o.classification_method.as_ref() == Some(&method))
+                .count()
+        };
+        let blank = observations
+            .iter()
+            .filter(|o| o.observation_type == ObservationType::Blank)
+            .count();
+
+        Summary {
+            deployments: self.deployments.len(),
+            start: self.deployments.iter().map(|d| d.deployment_start).min(),
+            end: self.deployments.iter().map(|d| d.deployment_end).max(),
+            camera_days: self
+                .effort(&EffortOptions::default())
+                .iter()
+                .map(|e| e.camera_days)
+                .sum(),
+            media: self.media.len(),
+            media_per_capture_method,
+            observation_level,
+            observations: observations.len(),
+            observations_per_type,
+            top_species,
+            human_classified_percentage: percentage(
+                classified(ClassificationMethod::Human),
+                observations.len(),
+            ),
+            machine_classified_percentage: percentage(
+                classified(ClassificationMethod::Machine),
+                observations.len(),
+            ),
+            blank_percentage: percentage(blank, observations.len()),
+            deployments_with_timestamp_issues: self
+                .deployments
+                .iter()
+                .filter(|d| d.timestamp_issues == Some(true))
+                .map(|d| d.deployment_id.clone())
+                .collect(),
+        }
+    }
+}
+
+impl Summary {
+    /// Overview rows as label and value, shared by the Markdown and HTML renderings.
+    fn overview(&self) -> Vec<(&'static str, String)> {
+        let date = |d: &Option<DateTime<FixedOffset>>| {
+            d.map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| "-".to_string())
+        };
+        vec![
+            ("Deployments", self.deployments.to_string()),
+            ("Start", date(&self.start)),
+            ("End", date(&self.end)),
+            ("Camera-days", format!("{:.1}", self.camera_days)),
+            ("Media", self.media.to_string()),
+            (
+                "Observations",
+                format!(
+                    "{} ({}-level)",
+                    self.observations,
+                    name(&self.observation_level)
+                ),
+            ),
+            (
+                "Classified by humans",
+                format!("{:.1}%", self.human_classified_percentage),
+            ),
+            (
+                "Classified by machines",
+                format!("{:.1}%", self.machine_classified_percentage),
+            ),
+            ("Blank", format!("{:.1}%", self.blank_percentage)),
+            (
+                "Deployments with timestamp issues",
+                if self.deployments_with_timestamp_issues.is_empty() {
+                    "none".to_string()
+                } else {
+                    self.deployments_with_timestamp_issues.join(", ")
+                },
+            ),
+        ]
+    }
+
+    /// Render the summary as Markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("# Package summary\n\n| | |\n|---|---|\n");
+        for (label, value) in self.overview() {
+            let _ = writeln!(md, "| {} | {} |", label, value);
+        }
+
+        md.push_str("\n## Media per capture method\n\n| Capture method | Media |\n|---|---|\n");
+        for (method, n) in &self.media_per_capture_method {
+            let _ = writeln!(md, "| {} | {} |", method, n);
+        }
+
+        md.push_str("\n## Observations per type\n\n| Type | Observations |\n|---|---|\n");
+        for (observation_type, n) in &self.observations_per_type {
+            let _ = writeln!(md, "| {} | {} |", observation_type, n);
+        }
+
+        md.push_str(
+            "\n## Top species\n\n| Species | Observations | Individuals |\n|---|---|---|\n",
+        );
+        for species in &self.top_species {
+            let _ = writeln!(
+                md,
+                "| *{}* | {} | {} |",
+                species.scientific_name, species.observations, species.individuals
+            );
+        }
+
+        md
+    }
+
+    /// Render the summary as an HTML fragment.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<h1>Package summary</h1>\n<table>\n");
+        for (label, value) in self.overview() {
+            let _ = writeln!(
+                html,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                label,
+                escape_html(&value)
+            );
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Media per capture method</h2>\n<table>\n");
+        html.push_str("<tr><th>Capture method</th><th>Media</th></tr>\n");
+        for (method, n) in &self.media_per_capture_method {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape_html(method),
+                n
+            );
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Observations per type</h2>\n<table>\n");
+        html.push_str("<tr><th>Type</th><th>Observations</th></tr>\n");
+        for (observation_type, n) in &self.observations_per_type {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape_html(observation_type),
+                n
+            );
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Top species</h2>\n<table>\n");
+        html.push_str("<tr><th>Species</th><th>Observations</th><th>Individuals</th></tr>\n");
+        for species in &self.top_species {
+            let _ = writeln!(
+                html,
+                "<tr><td><i>{}</i></td><td>{}</td><td>{}</td></tr>",
+                escape_html(&species.scientific_name),
+                species.observations,
+                species.individuals
+            );
+        }
+        html.push_str("</table>\n");
+
+        html
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixture_summary() {
+        let summary = Package::from_dir("fixtures").unwrap().summary();
+        assert_eq!(summary.deployments, 4);
+        assert_eq!(summary.media, 423);
+        assert_eq!(summary.media_per_capture_method["activityDetection"], 420);
+        assert_eq!(summary.media_per_capture_method["timeLapse"], 3);
+        assert_eq!(summary.observation_level, ObservationLevel::Event);
+        assert_eq!(summary.observations, 36);
+        assert_eq!(summary.observations_per_type["blank"], 3);
+        assert_eq!(summary.top_species[0].scientific_name, "Anas platyrhynchos");
+        assert_eq!(summary.top_species[0].observations, 15);
+        assert!((summary.blank_percentage - 3.0 / 36.0 * 100.0).abs() < 1e-9);
+        assert!(summary.deployments_with_timestamp_issues.is_empty());
+    }
+
+    #[test]
+    fn render_summary() {
+        let summary = Package::from_dir("fixtures").unwrap().summary();
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["deployments"], 4);
+        assert_eq!(json["observationLevel"], "event");
+
+        let markdown = summary.to_markdown();
+        assert!(markdown.contains("| Deployments | 4 |"));
+        assert!(markdown.contains("| *Anas platyrhynchos* | 15 |"));
+
+        let html = summary.to_html();
+        assert!(html.contains("<tr><th>Deployments</th><td>4</td></tr>"));
+        assert!(html.contains("<i>Anas platyrhynchos</i>"));
+    }
+}
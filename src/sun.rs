@@ -0,0 +1,114 @@
+//! Sunrise and sunset times, computed locally from coordinates.
+//!
+//! Uses the sunrise equation as published by NOAA, which is accurate to about a minute for
+//! latitudes between the polar circles.
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
+use std::f64::consts::PI;
+
+/// Julian day of the J2000 epoch.
+const J2000: f64 = 2451545.0;
+
+/// Julian day of the Unix epoch.
+const UNIX_EPOCH: f64 = 2440587.5;
+
+/// Sunrise and sunset on a date at a location (latitude and longitude in decimal degrees,
+/// WGS84). Returns `None` during polar day or polar night.
+pub fn sunrise_sunset(
+    date: NaiveDate,
+    latitude: f64,
+    longitude: f64,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let (sin, cos, asin, acos) = (
+        |d: f64| d.to_radians().sin(),
+        |d: f64| d.to_radians().cos(),
+        |x: f64| x.asin().to_degrees(),
+        |x: f64| x.acos().to_degrees(),
+    );
+
+    let noon = Utc
+        .with_ymd_and_hms(date.year(), date.month(), date.day(), 12, 0, 0)
+        .single()?;
+    let julian_day = noon.timestamp() as f64 / 86400.0 + UNIX_EPOCH;
+
+    let n = (julian_day - J2000 + 0.0008).round();
+    let mean_solar_noon = n - longitude / 360.0;
+    let anomaly = (357.5291 + 0.98560028 * mean_solar_noon).rem_euclid(360.0);
+    let center = 1.9148 * sin(anomaly) + 0.02 * sin(2.0 * anomaly) + 0.0003 * sin(3.0 * anomaly);
+    let ecliptic_longitude = (anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+    let transit =
+        J2000 + mean_solar_noon + 0.0053 * sin(anomaly) - 0.0069 * sin(2.0 * ecliptic_longitude);
+    let declination = asin(sin(ecliptic_longitude) * sin(23.4397));
+
+    let cos_hour_angle =
+        (sin(-0.833) - sin(latitude) * sin(declination)) / (cos(latitude) * cos(declination));
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+    let hour_angle = acos(cos_hour_angle);
+
+    Some((
+        from_julian_day(transit - hour_angle / 360.0)?,
+        from_julian_day(transit + hour_angle / 360.0)?,
+    ))
+}
+
+fn from_julian_day(julian_day: f64) -> Option<DateTime<Utc>> {
+    let seconds = (julian_day - UNIX_EPOCH) * 86400.0;
+    Utc.timestamp_opt(seconds.round() as i64, 0).single()
+}
+
+/// Time of day of a timestamp relative to the sun, in radians: sunrise maps to π/2 and sunset
+/// to 3π/2, with daytime and nighttime each stretched linearly over π. Returns `None` during
+/// polar day or polar night.
+pub fn sun_time<Tz: TimeZone>(
+    timestamp: &DateTime<Tz>,
+    latitude: f64,
+    longitude: f64,
+) -> Option<f64> {
+    let timestamp = timestamp.with_timezone(&Utc);
+    let (sunrise, sunset) = sunrise_sunset(timestamp.date_naive(), latitude, longitude)?;
+    let day = (sunset - sunrise).num_seconds() as f64;
+    let night = 86400.0 - day;
+    let since_sunrise = (timestamp - sunrise).num_seconds() as f64;
+
+    let radians = if (0.0..=day).contains(&since_sunrise) {
+        PI / 2.0 + since_sunrise / day * PI
+    } else {
+        let since_sunset = (since_sunrise - day).rem_euclid(86400.0);
+        3.0 * PI / 2.0 + since_sunset / night * PI
+    };
+    Some(radians.rem_euclid(2.0 * PI))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sunrise_sunset_brussels() {
+        // Brussels on the summer solstice: sunrise 03:29 UTC, sunset 19:59 UTC.
+        let date = NaiveDate::from_ymd_opt(2020, 6, 21).unwrap();
+        let (sunrise, sunset) = sunrise_sunset(date, 50.85, 4.35).unwrap();
+        let expected_sunrise = Utc.with_ymd_and_hms(2020, 6, 21, 3, 29, 0).unwrap();
+        let expected_sunset = Utc.with_ymd_and_hms(2020, 6, 21, 19, 59, 0).unwrap();
+        assert!((sunrise - expected_sunrise).num_seconds().abs() < 180);
+        assert!((sunset - expected_sunset).num_seconds().abs() < 180);
+    }
+
+    #[test]
+    fn polar_day() {
+        let date = NaiveDate::from_ymd_opt(2020, 6, 21).unwrap();
+        assert_eq!(sunrise_sunset(date, 78.2, 15.6), None);
+    }
+
+    #[test]
+    fn sun_time_anchors() {
+        let date = NaiveDate::from_ymd_opt(2020, 6, 21).unwrap();
+        let (sunrise, sunset) = sunrise_sunset(date, 50.85, 4.35).unwrap();
+        let at_sunrise = sun_time(&sunrise, 50.85, 4.35).unwrap();
+        let at_sunset = sun_time(&sunset, 50.85, 4.35).unwrap();
+        assert!((at_sunrise - PI / 2.0).abs() < 1e-3);
+        assert!((at_sunset - 3.0 * PI / 2.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,219 @@
+//! Typed access to the pipe (|) separated list fields of the Camera Trap Data Package.
+//!
+//! Fields such as `deploymentGroups`, `deploymentTags`, `behavior` and `observationTags` are
+//! stored as a single string (e.g. `area:HS | season:spring`). This module parses them into an
+//! ordered list of [`Tag`]s and writes them back in canonical form.
+
+use crate::{Deployment, Observation};
+use std::fmt;
+use std::str::FromStr;
+
+/// Separator used between values when writing a list in canonical form.
+const SEPARATOR: &str = " | ";
+
+/// A single value of a pipe separated list, optionally formatted as a key:value pair.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tag {
+    /// Key of the tag, if the value was formatted as a key:value pair.
+    pub key: Option<String>,
+
+    /// Value of the tag.
+    pub value: String,
+}
+
+impl Tag {
+    /// Create a new tag from an optional key and a value.
+    pub fn new(key: Option<&str>, value: &str) -> Self {
+        Tag {
+            key: key.map(|key| key.to_string()),
+            value: value.to_string(),
+        }
+    }
+
+    /// Parse a single tag. Splits on the first colon, so `position:above stream` has the key
+    /// `position` and the value `above stream`, while `foraging` has no key. A value with an
+    /// empty key (e.g. `:foo`) is kept whole.
+    pub fn parse(s: &str) -> Self {
+        match s.split_once(':') {
+            Some((key, value)) if !key.trim().is_empty() => Tag {
+                key: Some(key.trim().to_string()),
+                value: value.trim().to_string(),
+            },
+            _ => Tag {
+                key: None,
+                value: s.trim().to_string(),
+            },
+        }
+    }
+}
+
+impl FromStr for Tag {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Tag::parse(s))
+    }
+}
+
+impl From<&str> for Tag {
+    fn from(s: &str) -> Self {
+        Tag::parse(s)
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "{}:{}", key, self.value),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+/// Parse a pipe separated list into tags, preserving their order. Empty values are skipped.
+pub fn parse_tags(s: &str) -> Vec<Tag> {
+    s.split('|')
+        .filter(|part| !part.trim().is_empty())
+        .map(Tag::parse)
+        .collect()
+}
+
+/// Write tags as a pipe separated list in canonical form (`a:b | c:d`). Returns `None` for an
+/// empty list, so the field is left empty rather than written as an empty string.
+pub fn format_tags(tags: &[Tag]) -> Option<String> {
+    if tags.is_empty() {
+        return None;
+    }
+
+    Some(
+        tags.iter()
+            .map(|tag| tag.to_string())
+            .collect::<Vec<_>>()
+            .join(SEPARATOR),
+    )
+}
+
+fn parse_optional(s: &Option<String>) -> Vec<Tag> {
+    s.as_deref().map(parse_tags).unwrap_or_default()
+}
+
+impl Deployment {
+    /// Deployment group(s) associated with the deployment, in the order they were listed.
+    pub fn parsed_deployment_groups(&self) -> Vec<Tag> {
+        parse_optional(&self.deployment_groups)
+    }
+
+    /// Replace the deployment group(s), writing them in canonical form.
+    pub fn set_deployment_groups(&mut self, groups: &[Tag]) {
+        self.deployment_groups = format_tags(groups);
+    }
+
+    /// Tag(s) associated with the deployment, in the order they were listed.
+    pub fn parsed_tags(&self) -> Vec<Tag> {
+        parse_optional(&self.tags)
+    }
+
+    /// Replace the tag(s), writing them in canonical form.
+    pub fn set_tags(&mut self, tags: &[Tag]) {
+        self.tags = format_tags(tags);
+    }
+}
+
+impl Observation {
+    /// Behavior(s) of the observed individual(s), with the dominant behavior first.
+    pub fn behaviors(&self) -> Vec<Tag> {
+        parse_optional(&self.behavior)
+    }
+
+    /// Dominant behavior of the observed individual(s), if any.
+    pub fn dominant_behavior(&self) -> Option<Tag> {
+        self.behaviors().into_iter().next()
+    }
+
+    /// Replace the behavior(s), writing them in canonical form. The first behavior is
+    /// considered the dominant one.
+    pub fn set_behaviors(&mut self, behaviors: &[Tag]) {
+        self.behavior = format_tags(behaviors);
+    }
+
+    /// Tag(s) associated with the observation, in the order they were listed.
+    pub fn parsed_observation_tags(&self) -> Vec<Tag> {
+        parse_optional(&self.observation_tags)
+    }
+
+    /// Replace the observation tag(s), writing them in canonical form.
+    pub fn set_observation_tags(&mut self, tags: &[Tag]) {
+        self.observation_tags = format_tags(tags);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ReadDataPackageCsv;
+
+    #[test]
+    fn parse_key_value_tags() {
+        let tags = parse_tags("area:HS | season:spring");
+        assert_eq!(
+            tags,
+            vec![
+                Tag::new(Some("area"), "HS"),
+                Tag::new(Some("season"), "spring")
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_plain_and_mixed_tags() {
+        let tags = parse_tags("walking|position: above stream||");
+        assert_eq!(
+            tags,
+            vec![
+                Tag::new(None, "walking"),
+                Tag::new(Some("position"), "above stream")
+            ]
+        );
+
+        let tag = Tag::parse(" :foo ");
+        assert_eq!(tag, Tag::new(None, ":foo"));
+        assert_eq!(Tag::parse(&tag.to_string()), tag);
+    }
+
+    #[test]
+    fn format_canonical() {
+        let tags = parse_tags("area:HS|season :spring");
+        assert_eq!(
+            format_tags(&tags),
+            Some("area:HS | season:spring".to_string())
+        );
+        assert_eq!(format_tags(&[]), None);
+    }
+
+    #[test]
+    fn filter_deployments_by_group() {
+        let deployments = Deployment::from_file("fixtures/deployments.csv").unwrap();
+        let spring = Tag::from("season:spring");
+        let matching = deployments
+            .iter()
+            .filter(|d| d.parsed_deployment_groups().contains(&spring))
+            .count();
+        assert_eq!(matching, 2);
+    }
+
+    #[test]
+    fn dominant_behavior() {
+        let observations = Observation::from_file("fixtures/observations.csv").unwrap();
+        let mut observation = observations
+            .into_iter()
+            .find(|o| o.behavior.is_some())
+            .unwrap();
+        assert_eq!(
+            observation.dominant_behavior(),
+            Some(Tag::new(None, "foraging"))
+        );
+
+        observation.set_behaviors(&parse_tags("running|vigilance"));
+        assert_eq!(observation.behavior.as_deref(), Some("running | vigilance"));
+    }
+}
@@ -0,0 +1,436 @@
+//! Import of Timelapse2 CSV exports.
+//!
+//! Timelapse2 exports one row per image with the standard columns `File`, `RelativePath`,
+//! `DateTime` and `DeleteFlag`, followed by the data fields defined in the project template
+//! (e.g. `Species` and `Count`). Which of those fields hold species and counts is configured
+//! with a [`TimelapseMapping`]. Deployments are derived from the folder structure.
+
+use crate::util::media_type;
+use crate::{
+    ClassificationMethod, Deployment, Medium, Observation, ObservationLevel, ObservationType,
+    Package,
+};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+use std::collections::{BTreeMap, HashMap};
+
+/// Species field of a Timelapse2 template, with its optional count field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeciesColumn {
+    /// Column holding the species (or `human`, `vehicle`, blank values, ...).
+    pub species: String,
+
+    /// Column holding the number of individuals.
+    pub count: Option<String>,
+}
+
+/// Mapping of a Timelapse2 template onto Camtrap DP.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelapseMapping {
+    /// Species fields. Each non-blank species field of an image becomes an observation.
+    pub species_columns: Vec<SpeciesColumn>,
+
+    /// Column holding comments, copied to `observationComments`.
+    pub comments_column: Option<String>,
+
+    /// Scientific names of the species values (e.g. `Mallard` → `Anas platyrhynchos`).
+    /// Values not in the lookup are used as they are.
+    pub species_lookup: HashMap<String, String>,
+
+    /// Species values (case-insensitive) meaning the image is blank.
+    pub blank_values: Vec<String>,
+
+    /// Species values (case-insensitive) meaning a human.
+    pub human_values: Vec<String>,
+
+    /// Species values (case-insensitive) meaning a vehicle.
+    pub vehicle_values: Vec<String>,
+
+    /// Number of leading folders of `RelativePath` that identify a deployment. The first
+    /// folder is used as the location. `None` uses the whole relative path.
+    pub deployment_depth: Option<usize>,
+
+    /// UTC offset of the image date and times, which Timelapse2 stores without one.
+    pub utc_offset: FixedOffset,
+
+    /// Prefix of `filePath` (e.g. the root folder or base URL of the images).
+    pub media_root: Option<String>,
+
+    /// If true, images without any species value get a `blank` observation.
+    pub blanks: bool,
+}
+
+impl Default for TimelapseMapping {
+    fn default() -> Self {
+        TimelapseMapping {
+            species_columns: vec![SpeciesColumn {
+                species: "Species".to_string(),
+                count: Some("Count".to_string()),
+            }],
+            comments_column: Some("Comments".to_string()),
+            species_lookup: HashMap::new(),
+            blank_values: vec!["empty".to_string(), "blank".to_string(), "none".to_string()],
+            human_values: vec!["human".to_string(), "person".to_string()],
+            vehicle_values: vec!["vehicle".to_string()],
+            deployment_depth: None,
+            utc_offset: FixedOffset::east_opt(0).unwrap(),
+            media_root: None,
+            blanks: true,
+        }
+    }
+}
+
+/// Error type for importing Timelapse2 CSV exports.
+#[derive(Debug)]
+pub enum TimelapseError {
+    Csv(csv::Error),
+    MissingColumn(String),
+    InvalidDateTime { file: String, value: String },
+}
+
+/// Date and time formats of Timelapse2: `DateTime` in recent versions, `Date` and `Time` in
+/// older ones.
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%d-%b-%Y %H:%M:%S",
+];
+
+fn parse_datetime(value: &str, offset: &FixedOffset) -> Option<DateTime<FixedOffset>> {
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value.trim(), format).ok())
+        .and_then(|naive| offset.from_local_datetime(&naive).single())
+}
+
+impl TimelapseMapping {
+    fn observation_type(&self, value: &str) -> ObservationType {
+        let matches = |values: &[String]| values.iter().any(|v| v.eq_ignore_ascii_case(value));
+        if matches(&self.blank_values) {
+            ObservationType::Blank
+        } else if matches(&self.human_values) {
+            ObservationType::Human
+        } else if matches(&self.vehicle_values) {
+            ObservationType::Vehicle
+        } else {
+            ObservationType::Animal
+        }
+    }
+
+    /// Deployment (folder path) and location (first folder) of an image.
+    fn deployment(&self, relative_path: &str) -> (String, String) {
+        let folders: Vec<&str> = relative_path
+            .split(['/', '\\'])
+            .filter(|folder| !folder.is_empty())
+            .collect();
+        let depth = self.deployment_depth.unwrap_or(folders.len()).max(1);
+        let deployment = folders
+            .iter()
+            .take(depth)
+            .copied()
+            .collect::<Vec<_>>()
+            .join("/");
+        let deployment = if deployment.is_empty() {
+            "root".to_string()
+        } else {
+            deployment
+        };
+        let location = folders
+            .first()
+            .map(|folder| folder.to_string())
+            .unwrap_or_else(|| deployment.clone());
+        (deployment, location)
+    }
+}
+
+impl Package {
+    /// Package from a Timelapse2 CSV export. Images flagged for deletion are skipped. Media
+    /// IDs are the relative paths of the images and observations are media-based,
+    /// classified by a human, with IDs `<mediaID>_<n>`.
+    pub fn from_timelapse(path: &str, mapping: &TimelapseMapping) -> Result<Self, TimelapseError> {
+        let mut rdr = csv::Reader::from_path(path).map_err(TimelapseError::Csv)?;
+        let headers = rdr.headers().map_err(TimelapseError::Csv)?.clone();
+        let index = |name: &str| headers.iter().position(|header| header == name);
+        let required =
+            |name: &str| index(name).ok_or_else(|| TimelapseError::MissingColumn(name.to_string()));
+
+        let file_column = required("File")?;
+        let path_column = index("RelativePath");
+        let datetime_columns = match index("DateTime") {
+            Some(column) => (column, None),
+            None => (required("Date")?, Some(required("Time")?)),
+        };
+        let delete_column = index("DeleteFlag");
+        let comments_column = mapping.comments_column.as_deref().and_then(index);
+        let species_columns = mapping
+            .species_columns
+            .iter()
+            .map(|column| {
+                Ok((
+                    required(&column.species)?,
+                    match &column.count {
+                        Some(count) => Some(required(count)?),
+                        None => None,
+                    },
+                ))
+            })
+            .collect::<Result<Vec<_>, TimelapseError>>()?;
+
+        let mut package = Package::default();
+        let mut deployments: BTreeMap<String, (String, Vec<DateTime<FixedOffset>>)> =
+            BTreeMap::new();
+
+        for result in rdr.records() {
+            let record = result.map_err(TimelapseError::Csv)?;
+            let field = |column: usize| record.get(column).unwrap_or_default().trim();
+            let optional = |column: Option<usize>| {
+                column
+                    .map(field)
+                    .filter(|value| !value.is_empty())
+                    .map(|value| value.to_string())
+            };
+
+            if delete_column.is_some_and(|column| field(column).eq_ignore_ascii_case("true")) {
+                continue;
+            }
+
+            let file = field(file_column).to_string();
+            let relative_path = path_column.map(field).unwrap_or_default();
+            let datetime = match datetime_columns {
+                (date, Some(time)) => format!("{} {}", field(date), field(time)),
+                (datetime, None) => field(datetime).to_string(),
+            };
+            let timestamp = parse_datetime(&datetime, &mapping.utc_offset).ok_or_else(|| {
+                TimelapseError::InvalidDateTime {
+                    file: file.clone(),
+                    value: datetime.clone(),
+                }
+            })?;
+
+            let (deployment_id, location_id) = mapping.deployment(relative_path);
+            deployments
+                .entry(deployment_id.clone())
+                .or_insert_with(|| (location_id, Vec::new()))
+                .1
+                .push(timestamp);
+
+            let media_id = [relative_path.replace('\\', "/"), file.clone()]
+                .iter()
+                .filter(|part| !part.is_empty())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("/");
+            let file_path = match &mapping.media_root {
+                Some(root) => format!("{}/{}", root.trim_end_matches('/'), media_id),
+                None => media_id.clone(),
+            };
+            package.media.push(Medium {
+                media_id: media_id.clone(),
+                deployment_id: deployment_id.clone(),
+                capture_method: None,
+                timestamp,
+                file_path,
+                file_public: false,
+                file_name: Some(file.clone()),
+                file_mediatype: media_type(&file).to_string(),
+                exif_data: None,
+                favorite: None,
+                comments: None,
+            });
+
+            let mut observations: Vec<(ObservationType, Option<String>, Option<u32>)> =
+                species_columns
+                    .iter()
+                    .filter_map(|(species, count)| {
+                        let value = optional(Some(*species))?;
+                        let observation_type = mapping.observation_type(&value);
+                        let scientific_name = (observation_type == ObservationType::Animal)
+                            .then(|| mapping.species_lookup.get(&value).cloned().unwrap_or(value));
+                        let count = optional(*count).and_then(|count| count.parse().ok());
+                        Some((observation_type, scientific_name, count))
+                    })
+                    .collect();
+            if observations.is_empty() && mapping.blanks {
+                observations.push((ObservationType::Blank, None, None));
+            }
+
+            for (n, (observation_type, scientific_name, count)) in
+                observations.into_iter().enumerate()
+            {
+                package.observations.push(Observation {
+                    observation_id: format!("{}_{}", media_id, n + 1),
+                    deployment_id: deployment_id.clone(),
+                    media_id: Some(media_id.clone()),
+                    event_id: None,
+                    event_start: Some(timestamp),
+                    event_end: Some(timestamp),
+                    observation_level: ObservationLevel::Media,
+                    observation_type,
+                    camera_setup_type: None,
+                    scientific_name,
+                    count,
+                    life_stage: None,
+                    sex: None,
+                    behavior: None,
+                    individual_id: None,
+                    individual_position_radius: None,
+                    individual_position_angle: None,
+                    individual_speed: None,
+                    bbox_x: None,
+                    bbox_y: None,
+                    bbox_width: None,
+                    bbox_height: None,
+                    classification_method: Some(ClassificationMethod::Human),
+                    classified_by: None,
+                    classification_timestamp: None,
+                    classification_probability: None,
+                    observation_tags: None,
+                    observation_comments: optional(comments_column),
+                });
+            }
+        }
+
+        for (deployment_id, (location_id, timestamps)) in deployments {
+            package.deployments.push(Deployment {
+                deployment_id,
+                location_id: Some(location_id.clone()),
+                location_name: Some(location_id),
+                latitude: None,
+                longitude: None,
+                location_radius: None,
+                deployment_start: *timestamps.iter().min().unwrap(),
+                deployment_end: *timestamps.iter().max().unwrap(),
+                setup_by: None,
+                camera_id: None,
+                camera_model: None,
+                camera_delay: None,
+                camera_height: None,
+                camera_depth: None,
+                camera_angle: None,
+                camera_heading: None,
+                detection_distance: None,
+                timestamp_issues: None,
+                bait_use: None,
+                feature_type: None,
+                habitat: None,
+                deployment_groups: None,
+                tags: None,
+                comments: None,
+            });
+        }
+
+        Ok(package)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::test::temp_path;
+
+    /// Import a Timelapse export written to a temporary file.
+    fn import(
+        name: &str,
+        content: &str,
+        mapping: &TimelapseMapping,
+    ) -> Result<Package, TimelapseError> {
+        let path = temp_path(name);
+        std::fs::write(&path, content).unwrap();
+        let package = Package::from_timelapse(&path, mapping);
+        std::fs::remove_file(&path).unwrap();
+        package
+    }
+
+    #[test]
+    fn import_timelapse() {
+        let content = "File,RelativePath,Folder,DateTime,ImageQuality,DeleteFlag,Species,Count,Species2,Count2,Comments\n\
+             IMG_0001.JPG,SiteA\\Cam1,Survey,2021-03-01 06:15:00,Ok,false,Mallard,2,Grey heron,1,\n\
+             IMG_0002.JPG,SiteA\\Cam1,Survey,2021-03-01 06:15:05,Ok,false,,,,,\n\
+             IMG_0003.JPG,SiteA\\Cam1,Survey,2021-03-01 06:15:10,Ok,true,Mallard,1,,,\n\
+             IMG_0001.JPG,SiteB\\Cam2,Survey,2021-03-04 22:00:00,Dark,false,Person,1,,,walking dog\n";
+        let mapping = TimelapseMapping {
+            species_columns: vec![
+                SpeciesColumn {
+                    species: "Species".to_string(),
+                    count: Some("Count".to_string()),
+                },
+                SpeciesColumn {
+                    species: "Species2".to_string(),
+                    count: Some("Count2".to_string()),
+                },
+            ],
+            species_lookup: HashMap::from([(
+                "Mallard".to_string(),
+                "Anas platyrhynchos".to_string(),
+            )]),
+            utc_offset: FixedOffset::east_opt(3600).unwrap(),
+            ..Default::default()
+        };
+        let package = import("timelapse.csv", content, &mapping).unwrap();
+
+        let ids: Vec<&str> = package
+            .deployments
+            .iter()
+            .map(|d| d.deployment_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["SiteA/Cam1", "SiteB/Cam2"]);
+        assert_eq!(package.deployments[0].location_id.as_deref(), Some("SiteA"));
+        assert_eq!(
+            package.deployments[0].deployment_end.to_rfc3339(),
+            "2021-03-01T06:15:05+01:00"
+        );
+        assert_eq!(package.media.len(), 3);
+        assert_eq!(package.observations.len(), 4);
+
+        let mallard = &package.observations[0];
+        assert_eq!(mallard.observation_id, "SiteA/Cam1/IMG_0001.JPG_1");
+        assert_eq!(
+            mallard.scientific_name.as_deref(),
+            Some("Anas platyrhynchos")
+        );
+        assert_eq!(mallard.count, Some(2));
+        assert_eq!(
+            package.observations[1].scientific_name.as_deref(),
+            Some("Grey heron")
+        );
+        assert_eq!(
+            package.observations[2].observation_type,
+            ObservationType::Blank
+        );
+        assert_eq!(
+            package.observations[3].observation_type,
+            ObservationType::Human
+        );
+        assert_eq!(
+            package.observations[3].observation_comments.as_deref(),
+            Some("walking dog")
+        );
+    }
+
+    #[test]
+    fn import_timelapse_errors() {
+        assert!(matches!(
+            import(
+                "timelapse_no_species.csv",
+                "File,RelativePath,DateTime,DeleteFlag\nIMG_0001.JPG,A,2021-03-01 06:15:00,false\n",
+                &TimelapseMapping::default(),
+            ),
+            Err(TimelapseError::MissingColumn(column)) if column == "Species"
+        ));
+
+        let mapping = TimelapseMapping {
+            species_columns: vec![SpeciesColumn {
+                species: "Species".to_string(),
+                count: None,
+            }],
+            ..Default::default()
+        };
+        assert!(matches!(
+            import(
+                "timelapse_bad_date.csv",
+                "File,RelativePath,Date,Time,Species\nIMG_0001.JPG,A,1 March,06:15:00,\n",
+                &mapping,
+            ),
+            Err(TimelapseError::InvalidDateTime { .. })
+        ));
+    }
+}
@@ -0,0 +1,194 @@
+//! Timezone normalisation and local wall-clock times.
+//!
+//! Timestamps are stored with a fixed UTC offset, which may differ between resources (e.g.
+//! `+02:00` in media and `Z` in observations). This module normalises a package to UTC, or to
+//! the IANA timezone of each deployment, in which case every timestamp gets the offset that
+//! was in effect at that moment (so daylight saving time transitions are handled).
+//!
+//! With the `tzf` feature, [`tzf_rs::DefaultFinder`] looks up the timezone of each deployment
+//! from its coordinates in an offline copy of the timezone boundaries.
+
+use crate::Package;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, Utc};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+
+/// Lookup of the IANA timezone of a location, e.g. backed by an offline timezone boundary
+/// database.
+pub trait TimezoneLookup {
+    /// Timezone at the given latitude and longitude (decimal degrees, WGS84).
+    fn lookup(&self, latitude: f64, longitude: f64) -> Option<Tz>;
+}
+
+/// A single timezone applies to every location.
+impl TimezoneLookup for Tz {
+    fn lookup(&self, _latitude: f64, _longitude: f64) -> Option<Tz> {
+        Some(*self)
+    }
+}
+
+impl<F> TimezoneLookup for F
+where
+    F: Fn(f64, f64) -> Option<Tz>,
+{
+    fn lookup(&self, latitude: f64, longitude: f64) -> Option<Tz> {
+        self(latitude, longitude)
+    }
+}
+
+/// Timezone boundaries bundled with `tzf-rs`. Locations at sea get an `Etc/GMT` zone.
+#[cfg(feature = "tzf")]
+impl TimezoneLookup for tzf_rs::DefaultFinder {
+    fn lookup(&self, latitude: f64, longitude: f64) -> Option<Tz> {
+        self.get_tz_name(longitude, latitude).parse().ok()
+    }
+}
+
+/// Timestamp converted to a timezone, keeping the offset in effect at that moment.
+pub fn to_timezone(timestamp: &DateTime<FixedOffset>, timezone: &Tz) -> DateTime<FixedOffset> {
+    let local = timestamp.with_timezone(timezone);
+    local.with_timezone(&local.offset().fix())
+}
+
+/// Local wall-clock time of a timestamp in a timezone.
+pub fn local_time(timestamp: &DateTime<FixedOffset>, timezone: &Tz) -> NaiveDateTime {
+    timestamp.with_timezone(timezone).naive_local()
+}
+
+fn to_utc(timestamp: &DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+    timestamp.with_timezone(&Utc.fix())
+}
+
+impl Package {
+    /// Timezone of each deployment with coordinates, keyed by deploymentID.
+    pub fn deployment_timezones(&self, lookup: &dyn TimezoneLookup) -> HashMap<String, Tz> {
+        self.deployments
+            .iter()
+            .filter_map(|deployment| {
+                let timezone = lookup.lookup(deployment.latitude?, deployment.longitude?)?;
+                Some((deployment.deployment_id.clone(), timezone))
+            })
+            .collect()
+    }
+
+    /// Express all timestamps of the package in UTC.
+    pub fn normalize_to_utc(&mut self) {
+        self.map_timestamps(|_, timestamp| to_utc(&timestamp));
+    }
+
+    /// Express all timestamps of each deployment in its timezone. Timestamps of deployments
+    /// without a timezone are left untouched.
+    pub fn normalize_to_timezones(&mut self, timezones: &HashMap<String, Tz>) {
+        self.map_timestamps(
+            |deployment_id, timestamp| match timezones.get(deployment_id) {
+                Some(timezone) => to_timezone(&timestamp, timezone),
+                None => timestamp,
+            },
+        );
+    }
+
+    /// Apply a conversion to every timestamp, given the deploymentID it belongs to.
+    fn map_timestamps(&mut self, f: impl Fn(&str, DateTime<FixedOffset>) -> DateTime<FixedOffset>) {
+        for deployment in &mut self.deployments {
+            deployment.deployment_start = f(&deployment.deployment_id, deployment.deployment_start);
+            deployment.deployment_end = f(&deployment.deployment_id, deployment.deployment_end);
+        }
+        for medium in &mut self.media {
+            medium.timestamp = f(&medium.deployment_id, medium.timestamp);
+        }
+        for observation in &mut self.observations {
+            let deployment_id = &observation.deployment_id;
+            observation.event_start = observation.event_start.map(|t| f(deployment_id, t));
+            observation.event_end = observation.event_end.map(|t| f(deployment_id, t));
+            observation.classification_timestamp = observation
+                .classification_timestamp
+                .map(|t| f(deployment_id, t));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Timelike;
+
+    #[test]
+    fn daylight_saving_time() {
+        let timezone = chrono_tz::Europe::Brussels;
+        let winter = DateTime::parse_from_rfc3339("2021-03-27T12:00:00Z").unwrap();
+        let summer = DateTime::parse_from_rfc3339("2021-03-28T12:00:00Z").unwrap();
+
+        assert_eq!(
+            to_timezone(&winter, &timezone).to_rfc3339(),
+            "2021-03-27T13:00:00+01:00"
+        );
+        assert_eq!(
+            to_timezone(&summer, &timezone).to_rfc3339(),
+            "2021-03-28T14:00:00+02:00"
+        );
+        assert_eq!(local_time(&summer, &timezone).hour(), 14);
+    }
+
+    #[test]
+    fn normalize_package() {
+        let original = Package::from_dir("fixtures").unwrap();
+
+        let mut utc = original.clone();
+        utc.normalize_to_utc();
+        assert!(utc
+            .media
+            .iter()
+            .all(|m| m.timestamp.offset().local_minus_utc() == 0));
+        assert_eq!(utc.media[0].timestamp, original.media[0].timestamp);
+
+        let timezones = original.deployment_timezones(&chrono_tz::Europe::Brussels);
+        assert_eq!(timezones.len(), 4);
+
+        let mut local = original.clone();
+        local.normalize_to_timezones(&timezones);
+        let observation = local
+            .observations
+            .iter()
+            .find(|o| o.event_start.is_some())
+            .unwrap();
+        assert_eq!(
+            observation.event_start.unwrap().offset().local_minus_utc(),
+            2 * 3600
+        );
+
+        // Deployment 62c200a9 spans the start of daylight saving time on 2021-03-28.
+        let deployment = local.deployment("62c200a9").unwrap();
+        assert_eq!(deployment.deployment_start.offset().local_minus_utc(), 3600);
+        assert_eq!(deployment.deployment_end.offset().local_minus_utc(), 7200);
+    }
+
+    #[test]
+    fn lookup_from_closure() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let lookup = |latitude: f64, _longitude: f64| {
+            (latitude > 51.0).then_some(chrono_tz::Europe::Amsterdam)
+        };
+        assert_eq!(package.deployment_timezones(&lookup).len(), 3);
+    }
+
+    #[cfg(feature = "tzf")]
+    #[test]
+    fn lookup_from_boundaries() {
+        let package = Package::from_dir("fixtures").unwrap();
+        let timezones = package.deployment_timezones(&tzf_rs::DefaultFinder::new());
+        assert_eq!(timezones.len(), 4);
+        assert!(timezones
+            .values()
+            .all(|timezone| *timezone == chrono_tz::Europe::Brussels));
+
+        let finder = tzf_rs::DefaultFinder::new();
+        assert_eq!(
+            finder.lookup(-1.2921, 36.8219),
+            Some(chrono_tz::Africa::Nairobi)
+        );
+        assert_eq!(
+            finder.lookup(40.7128, -74.006),
+            Some(chrono_tz::America::New_York)
+        );
+    }
+}
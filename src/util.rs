@@ -0,0 +1,63 @@
+//! Helpers shared by the exporters.
+
+use serde::Serialize;
+
+/// Name of a serialized enum value (e.g. `activityDetection`).
+pub(crate) fn name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(|s| s.to_string()))
+        .unwrap_or_default()
+}
+
+/// Media type of a media file, from its extension. JPEG if the extension is not recognised.
+pub(crate) fn media_type(file: &str) -> &'static str {
+    let extension = file.rsplit('.').next().unwrap_or_default();
+    match extension.to_lowercase().as_str() {
+        "png" => "image/png",
+        "tif" | "tiff" => "image/tiff",
+        "gif" => "image/gif",
+        "mp4" => "video/mp4",
+        "avi" => "video/x-msvideo",
+        "mov" => "video/quicktime",
+        _ => "image/jpeg",
+    }
+}
+
+/// Escape text for use in XML content and attribute values.
+pub(crate) fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// Path in the temporary directory, unique to this test process.
+    pub(crate) fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("camtrap_dp_{}_{}", std::process::id(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn xml_escaping() {
+        assert_eq!(
+            escape_xml(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn media_types() {
+        assert_eq!(media_type("a/IMG_0001.JPG"), "image/jpeg");
+        assert_eq!(media_type("a/IMG_0001.tif"), "image/tiff");
+        assert_eq!(media_type("a/VID_0001.MP4"), "video/mp4");
+        assert_eq!(media_type("a/VID_0001.mov"), "video/quicktime");
+    }
+}
@@ -0,0 +1,709 @@
+//! Export to the Wildlife Insights bulk upload templates: `projects.csv`, `cameras.csv`,
+//! `deployments.csv` and `images.csv`.
+//!
+//! Wildlife Insights identifies taxa by its own taxonomy. Scientific names are mapped onto it
+//! with a local copy of that taxonomy (see [`WiTaxonomy`]). Records that cannot be mapped are
+//! left out of the templates and reported instead.
+
+use crate::metadata::PackageMetadata;
+use crate::util::name;
+use crate::{
+    Deployment, Medium, Observation, ObservationLevel, ObservationType, Package,
+    WriteDataPackageCsv,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// Date and time format of the templates.
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Taxon of the Wildlife Insights taxonomy, as in its CSV download.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct WiTaxon {
+    #[serde(rename = "uniqueIdentifier")]
+    pub unique_identifier: String,
+
+    #[serde(default)]
+    pub class: String,
+
+    #[serde(default)]
+    pub order: String,
+
+    #[serde(default)]
+    pub family: String,
+
+    #[serde(default)]
+    pub genus: String,
+
+    #[serde(default)]
+    pub species: String,
+
+    #[serde(rename = "commonNameEnglish", default)]
+    pub common_name_english: String,
+}
+
+impl WiTaxon {
+    /// Name of the taxon at its most specific rank (e.g. `Anas platyrhynchos` or
+    /// `Anatidae`).
+    pub fn scientific_name(&self) -> String {
+        if !self.genus.is_empty() && !self.species.is_empty() {
+            return format!("{} {}", self.genus, self.species);
+        }
+        [&self.genus, &self.family, &self.order, &self.class]
+            .into_iter()
+            .find(|name| !name.is_empty())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Local lookup table of the Wildlife Insights taxonomy.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WiTaxonomy {
+    by_name: HashMap<String, WiTaxon>,
+}
+
+impl WiTaxonomy {
+    /// Lookup table of the given taxa.
+    pub fn new(taxa: Vec<WiTaxon>) -> Self {
+        let mut by_name = HashMap::new();
+        for taxon in taxa {
+            let name = match taxon.scientific_name() {
+                name if name.is_empty() => taxon.common_name_english.clone(),
+                name => name,
+            };
+            by_name.entry(name.to_lowercase()).or_insert(taxon);
+        }
+        WiTaxonomy { by_name }
+    }
+
+    /// Read the taxonomy from a CSV file with at least the columns `uniqueIdentifier`,
+    /// `class`, `order`, `family`, `genus`, `species` and `commonNameEnglish`.
+    pub fn from_file(path: &str) -> Result<Self, csv::Error> {
+        let mut rdr = csv::Reader::from_path(path)?;
+        let taxa = rdr.deserialize().collect::<Result<Vec<WiTaxon>, _>>()?;
+        Ok(WiTaxonomy::new(taxa))
+    }
+
+    /// Taxon with the given scientific name (case-insensitive). Non-taxonomic entries such as
+    /// `Blank`, `Human` and `Vehicle` are found by their common name.
+    pub fn get(&self, name: &str) -> Option<&WiTaxon> {
+        self.by_name.get(&name.to_lowercase())
+    }
+
+    fn for_observation(&self, observation: &Observation) -> Option<&WiTaxon> {
+        match observation.observation_type {
+            ObservationType::Blank => self.get("Blank"),
+            ObservationType::Human => self.get("Homo sapiens").or_else(|| self.get("Human")),
+            ObservationType::Vehicle => self.get("Vehicle"),
+            _ => self.get(observation.scientific_name.as_deref()?),
+        }
+    }
+}
+
+/// Options for [`Package::to_wildlife_insights`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WiOptions {
+    /// Short identifier of the project on Wildlife Insights.
+    pub project_id: String,
+
+    /// ISO 3166-1 alpha-3 code of the country of the project.
+    pub country_code: Option<String>,
+
+    /// Number of months the data is embargoed after upload.
+    pub embargo: u32,
+}
+
+/// Row of `projects.csv`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct WiProject {
+    pub project_name: String,
+    pub project_id: String,
+    pub project_short_name: Option<String>,
+    pub project_objectives: Option<String>,
+    pub project_species: Option<String>,
+    pub project_species_individual: Option<String>,
+    pub project_sensor_layout: Option<String>,
+    pub project_sensor_layout_targeted_type: Option<String>,
+    pub project_bait_use: Option<String>,
+    pub project_bait_type: Option<String>,
+    pub project_stratification: Option<String>,
+    pub project_stratification_type: Option<String>,
+    pub project_sensor_method: Option<String>,
+    pub project_individual_animals: Option<String>,
+    pub project_admin: Option<String>,
+    pub project_admin_email: Option<String>,
+    pub project_admin_organization: Option<String>,
+    pub country_code: Option<String>,
+    pub embargo: u32,
+    pub initiative_id: Option<String>,
+    pub metadata_license: Option<String>,
+    pub image_license: Option<String>,
+}
+
+/// Row of `cameras.csv`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct WiCamera {
+    pub project_id: String,
+    pub camera_id: String,
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+    pub year_purchased: Option<String>,
+}
+
+/// Row of `deployments.csv`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct WiDeployment {
+    pub project_id: String,
+    pub deployment_id: String,
+    pub subproject_name: Option<String>,
+    pub subproject_design: Option<String>,
+    pub placename: String,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub start_date: String,
+    pub end_date: String,
+    pub event_name: Option<String>,
+    pub event_description: Option<String>,
+    pub event_type: Option<String>,
+    pub bait_type: String,
+    pub bait_description: Option<String>,
+    pub feature_type: String,
+    pub feature_type_methodology: Option<String>,
+    pub camera_id: String,
+    pub quiet_period: Option<f64>,
+    pub camera_functioning: String,
+    pub sensor_height: String,
+    pub height_other: Option<String>,
+    pub sensor_orientation: Option<String>,
+    pub orientation_other: Option<String>,
+    pub plot_treatment: Option<String>,
+    pub plot_treatment_description: Option<String>,
+    pub detection_distance: Option<f64>,
+    pub recorded_by: Option<String>,
+    pub remarks: Option<String>,
+}
+
+/// Row of `images.csv`: an identification in an image.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct WiImage {
+    pub project_id: String,
+    pub deployment_id: String,
+    pub image_id: String,
+    pub location: String,
+    pub is_blank: u8,
+    pub identified_by: Option<String>,
+    pub wi_taxon_id: String,
+    pub class: String,
+    pub order: String,
+    pub family: String,
+    pub genus: String,
+    pub species: String,
+    pub common_name: String,
+    pub uncertainty: Option<String>,
+    pub timestamp: String,
+    pub number_of_objects: Option<u32>,
+    pub highlighted: Option<bool>,
+    pub age: Option<String>,
+    pub sex: Option<String>,
+    pub animal_recognizable: Option<String>,
+    pub individual_id: Option<String>,
+    pub individual_animal_notes: Option<String>,
+    pub behavior: Option<String>,
+    pub remarks: Option<String>,
+    pub markings: Option<String>,
+}
+
+impl WriteDataPackageCsv<WiProject> for Vec<WiProject> {}
+impl WriteDataPackageCsv<WiCamera> for Vec<WiCamera> {}
+impl WriteDataPackageCsv<WiDeployment> for Vec<WiDeployment> {}
+impl WriteDataPackageCsv<WiImage> for Vec<WiImage> {}
+
+/// Record that could not be mapped onto the templates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WiUnmapped {
+    /// Template the record was meant for (e.g. `images.csv`).
+    pub template: &'static str,
+
+    /// Identifier of the record (deploymentID or observationID).
+    pub id: String,
+
+    /// Why the record could not be mapped.
+    pub reason: String,
+}
+
+/// Wildlife Insights bulk upload templates of a package.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WiExport {
+    pub projects: Vec<WiProject>,
+    pub cameras: Vec<WiCamera>,
+    pub deployments: Vec<WiDeployment>,
+    pub images: Vec<WiImage>,
+
+    /// Records left out of the templates.
+    pub unmapped: Vec<WiUnmapped>,
+}
+
+impl WiExport {
+    /// Write the templates to a directory, which must exist.
+    pub fn to_dir(&self, path: &str) -> Result<(), csv::Error> {
+        let dir = Path::new(path);
+        let file = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        self.projects.to_file(&file("projects.csv"))?;
+        self.cameras.to_file(&file("cameras.csv"))?;
+        self.deployments.to_file(&file("deployments.csv"))?;
+        self.images.to_file(&file("images.csv"))?;
+        Ok(())
+    }
+}
+
+/// Sentence case of a serialized enum value (e.g. `roadPaved` → `Road paved`).
+fn sentence_case(value: &str) -> String {
+    let mut sentence = String::new();
+    for (i, c) in value.chars().enumerate() {
+        if i == 0 {
+            sentence.extend(c.to_uppercase());
+        } else if c.is_uppercase() {
+            sentence.push(' ');
+            sentence.extend(c.to_lowercase());
+        } else {
+            sentence.push(c);
+        }
+    }
+    sentence
+}
+
+/// Wildlife Insights sensor layout of a Camtrap DP `samplingDesign`. `experimental` has no
+/// counterpart.
+fn sensor_layout(sampling_design: &str) -> Option<&'static str> {
+    match sampling_design {
+        "simpleRandom" | "systematicRandom" | "clusteredRandom" => Some("Randomized"),
+        "opportunistic" => Some("Convenience"),
+        "targeted" => Some("Targeted"),
+        _ => None,
+    }
+}
+
+/// Licenses accepted by Wildlife Insights.
+fn license(metadata: &PackageMetadata, scope: &str) -> Option<String> {
+    let license = metadata.license(scope)?;
+    let id = license.name.as_deref().or(license.path.as_deref())?;
+    Some(
+        if id.contains("CC0") || id.contains("publicdomain/zero") {
+            "CC0"
+        } else if id.contains("BY-NC") || id.contains("by-nc") {
+            "CC BY-NC 4.0"
+        } else {
+            "CC BY 4.0"
+        }
+        .to_string(),
+    )
+}
+
+impl Package {
+    /// Wildlife Insights bulk upload templates. Deployments without coordinates and
+    /// observations whose taxon is not in the taxonomy are reported as unmapped. Media-based
+    /// observations are used if the package has them, else event-based observations are
+    /// applied to every media file of their event.
+    pub fn to_wildlife_insights(&self, taxonomy: &WiTaxonomy, options: &WiOptions) -> WiExport {
+        let metadata = self.metadata.clone().unwrap_or_default();
+        let mut export = WiExport::default();
+
+        let admin = metadata
+            .contributors_with_role("contact")
+            .chain(metadata.contributors_with_role("principalInvestigator"))
+            .next()
+            .cloned();
+        let project = metadata.project.clone().unwrap_or_default();
+        export.projects.push(WiProject {
+            project_name: metadata
+                .title
+                .clone()
+                .unwrap_or_else(|| project.title.clone()),
+            project_id: options.project_id.clone(),
+            project_short_name: project.acronym.clone(),
+            project_objectives: project.description.clone(),
+            project_sensor_layout: project
+                .sampling_design
+                .as_deref()
+                .and_then(sensor_layout)
+                .map(str::to_string),
+            project_bait_use: Some(
+                if self.deployments.iter().any(|d| d.bait_use == Some(true)) {
+                    "Yes"
+                } else {
+                    "No"
+                }
+                .to_string(),
+            ),
+            project_sensor_method: Some("Sensor Detection".to_string()),
+            project_individual_animals: project
+                .individual_animals
+                .map(|individual| if individual { "Yes" } else { "No" }.to_string()),
+            project_admin: admin.as_ref().map(|c| c.title.clone()),
+            project_admin_email: admin.as_ref().and_then(|c| c.email.clone()),
+            project_admin_organization: admin.as_ref().and_then(|c| c.organization.clone()),
+            country_code: options.country_code.clone(),
+            embargo: options.embargo,
+            metadata_license: license(&metadata, "data"),
+            image_license: license(&metadata, "media"),
+            ..Default::default()
+        });
+
+        let mut cameras: BTreeMap<String, WiCamera> = BTreeMap::new();
+        let mut mapped_deployments: HashSet<&str> = HashSet::new();
+        for deployment in &self.deployments {
+            let camera_id = camera_id(deployment);
+            cameras.entry(camera_id.clone()).or_insert_with(|| {
+                let (make, model) = match deployment.camera_model.as_deref() {
+                    Some(model) => match model.split_once('-') {
+                        Some((make, model)) => (Some(make.to_string()), Some(model.to_string())),
+                        None => (None, Some(model.to_string())),
+                    },
+                    None => (None, None),
+                };
+                WiCamera {
+                    project_id: options.project_id.clone(),
+                    camera_id: camera_id.clone(),
+                    make,
+                    model,
+                    ..Default::default()
+                }
+            });
+
+            let (latitude, longitude) = match (deployment.latitude, deployment.longitude) {
+                (Some(latitude), Some(longitude)) => (latitude, longitude),
+                _ => {
+                    export.unmapped.push(WiUnmapped {
+                        template: "deployments.csv",
+                        id: deployment.deployment_id.clone(),
+                        reason: "missing coordinates".to_string(),
+                    });
+                    continue;
+                }
+            };
+            mapped_deployments.insert(&deployment.deployment_id);
+            export.deployments.push(WiDeployment {
+                project_id: options.project_id.clone(),
+                deployment_id: deployment.deployment_id.clone(),
+                placename: deployment
+                    .location_name
+                    .clone()
+                    .or_else(|| deployment.location_id.clone())
+                    .unwrap_or_else(|| deployment.deployment_id.clone()),
+                longitude,
+                latitude,
+                start_date: deployment
+                    .deployment_start
+                    .format(DATETIME_FORMAT)
+                    .to_string(),
+                end_date: deployment
+                    .deployment_end
+                    .format(DATETIME_FORMAT)
+                    .to_string(),
+                bait_type: match deployment.bait_use {
+                    Some(true) => "Other",
+                    _ => "None",
+                }
+                .to_string(),
+                feature_type: deployment
+                    .feature_type
+                    .as_ref()
+                    .map(|feature| sentence_case(&name(feature)))
+                    .unwrap_or_else(|| "None".to_string()),
+                camera_id,
+                quiet_period: deployment.camera_delay,
+                camera_functioning: "Camera Functioning".to_string(),
+                sensor_height: if deployment.camera_height.is_some() {
+                    "Other"
+                } else {
+                    "Unknown"
+                }
+                .to_string(),
+                height_other: deployment
+                    .camera_height
+                    .map(|height| format!("{} m", height)),
+                detection_distance: deployment.detection_distance,
+                recorded_by: deployment.setup_by.clone(),
+                remarks: deployment.comments.clone(),
+                ..Default::default()
+            });
+        }
+        export.cameras = cameras.into_values().collect();
+
+        for (medium, observation) in self.identifications() {
+            if !mapped_deployments.contains(medium.deployment_id.as_str()) {
+                export.unmapped.push(WiUnmapped {
+                    template: "images.csv",
+                    id: observation.observation_id.clone(),
+                    reason: format!("deployment not mapped: {}", medium.deployment_id),
+                });
+                continue;
+            }
+            let taxon = match taxonomy.for_observation(observation) {
+                Some(taxon) => taxon,
+                None => {
+                    export.unmapped.push(WiUnmapped {
+                        template: "images.csv",
+                        id: observation.observation_id.clone(),
+                        reason: format!(
+                            "taxon not in the Wildlife Insights taxonomy: {}",
+                            observation
+                                .scientific_name
+                                .clone()
+                                .unwrap_or_else(|| name(&observation.observation_type))
+                        ),
+                    });
+                    continue;
+                }
+            };
+            export.images.push(WiImage {
+                project_id: options.project_id.clone(),
+                deployment_id: medium.deployment_id.clone(),
+                image_id: medium.media_id.clone(),
+                location: medium.file_path.clone(),
+                is_blank: (observation.observation_type == ObservationType::Blank) as u8,
+                identified_by: observation.classified_by.clone(),
+                wi_taxon_id: taxon.unique_identifier.clone(),
+                class: taxon.class.clone(),
+                order: taxon.order.clone(),
+                family: taxon.family.clone(),
+                genus: taxon.genus.clone(),
+                species: taxon.species.clone(),
+                common_name: taxon.common_name_english.clone(),
+                timestamp: medium.timestamp.format(DATETIME_FORMAT).to_string(),
+                number_of_objects: observation.count,
+                highlighted: medium.favorite,
+                age: observation
+                    .life_stage
+                    .as_ref()
+                    .map(|stage| sentence_case(&name(stage))),
+                sex: observation
+                    .sex
+                    .as_ref()
+                    .map(|sex| sentence_case(&name(sex))),
+                individual_id: observation.individual_id.clone(),
+                behavior: observation.behavior.clone(),
+                remarks: observation.observation_comments.clone(),
+                ..Default::default()
+            });
+        }
+
+        export
+    }
+
+    /// Identifications per media file: media-based observations, or event-based observations
+    /// applied to the media files of their event.
+    fn identifications(&self) -> Vec<(&Medium, &Observation)> {
+        let media_level = self
+            .observations
+            .iter()
+            .any(|o| o.observation_level == ObservationLevel::Media);
+        if media_level {
+            return self
+                .observations
+                .iter()
+                .filter(|o| o.observation_level == ObservationLevel::Media)
+                .filter_map(|o| Some((self.medium(o.media_id.as_deref()?)?, o)))
+                .collect();
+        }
+
+        let mut by_deployment: HashMap<&str, Vec<&Medium>> = HashMap::new();
+        for medium in &self.media {
+            by_deployment
+                .entry(&medium.deployment_id)
+                .or_default()
+                .push(medium);
+        }
+        for media in by_deployment.values_mut() {
+            media.sort_by_key(|m| m.timestamp);
+        }
+
+        let mut identifications = Vec::new();
+        for observation in &self.observations {
+            let (start, end) = match (observation.event_start, observation.event_end) {
+                (Some(start), Some(end)) => (start, end),
+                _ => continue,
+            };
+            let media = match by_deployment.get(observation.deployment_id.as_str()) {
+                Some(media) => media,
+                None => continue,
+            };
+            let first = media.partition_point(|m| m.timestamp < start);
+            identifications.extend(
+                media[first..]
+                    .iter()
+                    .take_while(|m| m.timestamp <= end)
+                    .map(|m| (*m, observation)),
+            );
+        }
+        identifications
+    }
+}
+
+fn camera_id(deployment: &Deployment) -> String {
+    deployment
+        .camera_id
+        .clone()
+        .unwrap_or_else(|| deployment.deployment_id.clone())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::test::temp_path;
+
+    fn taxonomy() -> WiTaxonomy {
+        let path = temp_path("wi_taxonomy.csv");
+        std::fs::write(
+            &path,
+            "uniqueIdentifier,class,order,family,genus,species,commonNameEnglish\n\
+             f1856211-cfb7-4a5b-9158-c0f72fd09ee6,,,,,,Blank\n\
+             990ae9dd-7a59-4344-afcb-1b7b21368000,Mammalia,Primates,Hominidae,Homo,sapiens,Human\n\
+             cd7a2ba1-61b1-45d0-b2f5-0ee7fbd0de2c,Aves,Anseriformes,Anatidae,Anas,platyrhynchos,Mallard\n",
+        )
+        .unwrap();
+        let taxonomy = WiTaxonomy::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        taxonomy
+    }
+
+    #[test]
+    fn wildlife_insights_templates() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        package.metadata = Some(crate::metadata::test::example());
+        package.deployments[3].latitude = None;
+        let export = package.to_wildlife_insights(
+            &taxonomy(),
+            &WiOptions {
+                project_id: "mica".to_string(),
+                country_code: Some("BEL".to_string()),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            export.projects[0].project_short_name.as_deref(),
+            Some("MICA")
+        );
+        assert_eq!(export.projects[0].metadata_license.as_deref(), Some("CC0"));
+        assert_eq!(export.cameras.len(), 4);
+        assert_eq!(export.deployments.len(), 3);
+        assert_eq!(
+            export.unmapped[0],
+            WiUnmapped {
+                template: "deployments.csv",
+                id: "62c200a9".to_string(),
+                reason: "missing coordinates".to_string(),
+            }
+        );
+
+        let mallard = export
+            .images
+            .iter()
+            .find(|i| i.image_id == "07840dcc")
+            .unwrap();
+        assert_eq!(mallard.wi_taxon_id, "cd7a2ba1-61b1-45d0-b2f5-0ee7fbd0de2c");
+        assert_eq!(mallard.timestamp, "2020-05-30 04:57:37");
+        assert_eq!(mallard.age.as_deref(), Some("Adult"));
+        assert!(export
+            .unmapped
+            .iter()
+            .any(|u| u.template == "images.csv" && u.reason.ends_with("Ardea cinerea")));
+        assert!(export.images.iter().any(|i| i.is_blank == 1));
+        assert!(export.images.iter().all(|i| i.deployment_id != "62c200a9"));
+        let unmapped_deployment = export
+            .unmapped
+            .iter()
+            .filter(|u| u.template == "images.csv" && u.reason == "deployment not mapped: 62c200a9")
+            .count();
+        let identifications = package
+            .observations
+            .iter()
+            .filter(|o| {
+                o.deployment_id == "62c200a9" && o.observation_level == ObservationLevel::Media
+            })
+            .count();
+        assert!(identifications > 0);
+        assert_eq!(unmapped_deployment, identifications);
+
+        let dir = temp_path("wildlife_insights");
+        std::fs::create_dir_all(&dir).unwrap();
+        export.to_dir(&dir).unwrap();
+        let files = [
+            "projects.csv",
+            "cameras.csv",
+            "deployments.csv",
+            "images.csv",
+        ]
+        .map(|file| Path::new(&dir).join(file).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files, [true; 4]);
+    }
+
+    #[test]
+    fn taxon_scientific_names() {
+        let taxonomy = taxonomy();
+        let mallard = taxonomy.get("anas platyrhynchos").unwrap();
+        assert_eq!(mallard.scientific_name(), "Anas platyrhynchos");
+        assert_eq!(taxonomy.get("Blank").unwrap().scientific_name(), "");
+
+        let family = WiTaxon {
+            order: "Anseriformes".to_string(),
+            family: "Anatidae".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(family.scientific_name(), "Anatidae");
+    }
+
+    #[test]
+    fn event_identifications() {
+        let mut package = Package::from_dir("fixtures").unwrap();
+        package
+            .observations
+            .retain(|o| o.observation_level == ObservationLevel::Event);
+        let identifications = package.identifications();
+
+        let expected: usize = package
+            .observations
+            .iter()
+            .map(|o| {
+                package
+                    .media
+                    .iter()
+                    .filter(|m| {
+                        m.deployment_id == o.deployment_id
+                            && Some(m.timestamp) >= o.event_start
+                            && Some(m.timestamp) <= o.event_end
+                    })
+                    .count()
+            })
+            .sum();
+        assert!(expected > 0);
+        assert_eq!(identifications.len(), expected);
+        assert!(identifications
+            .iter()
+            .all(|(m, o)| m.deployment_id == o.deployment_id
+                && Some(m.timestamp) >= o.event_start
+                && Some(m.timestamp) <= o.event_end));
+    }
+
+    #[test]
+    fn sentence_case_values() {
+        assert_eq!(sentence_case("roadPaved"), "Road paved");
+        assert_eq!(sentence_case("female"), "Female");
+    }
+
+    #[test]
+    fn sensor_layouts() {
+        assert_eq!(sensor_layout("simpleRandom"), Some("Randomized"));
+        assert_eq!(sensor_layout("systematicRandom"), Some("Randomized"));
+        assert_eq!(sensor_layout("clusteredRandom"), Some("Randomized"));
+        assert_eq!(sensor_layout("opportunistic"), Some("Convenience"));
+        assert_eq!(sensor_layout("targeted"), Some("Targeted"));
+        assert_eq!(sensor_layout("experimental"), None);
+        assert_eq!(sensor_layout("unknown"), None);
+    }
+}